@@ -0,0 +1,336 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+/// How a typed answer is tidied up before it is compared with the accepted ones,
+/// every rule is on unless the room turns it off.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct Normalization
+{
+    pub ignore_case: bool,
+    /// Trims the answer and squeezes runs of spaces into one.
+    pub collapse_whitespace: bool,
+    /// Reads `ё` as `е`.
+    pub fold_yo: bool
+}
+
+impl Default for Normalization
+{
+    fn default() -> Self
+    {
+        Normalization { ignore_case: true, collapse_whitespace: true, fold_yo: true }
+    }
+}
+
+impl Normalization
+{
+    /// Takes the rules to keep, separated by commas or spaces: `case`, `whitespace` and `yo`,
+    /// or `none` for an exact match.
+    pub fn parse(text: &str) -> Option<Self>
+    {
+        let mut normalization = Normalization { ignore_case: false, collapse_whitespace: false, fold_yo: false };
+        for rule in text.split([',', ' ']).map(str::trim).filter(|rule| !rule.is_empty())
+        {
+            match rule
+            {
+                "case" => normalization.ignore_case = true,
+                "whitespace" => normalization.collapse_whitespace = true,
+                "yo" => normalization.fold_yo = true,
+                "none" => {},
+                _ => return None
+            }
+        }
+
+        Some(normalization)
+    }
+
+    pub fn apply(&self, text: &str) -> String
+    {
+        let mut text = if self.collapse_whitespace { text.split_whitespace().collect::<Vec<_>>().join(" ") } else { text.to_string() };
+
+        if self.ignore_case
+        {
+            text = text.to_lowercase();
+        }
+
+        if self.fold_yo
+        {
+            text = text.replace('ё', "е").replace('Ё', "Е");
+        }
+
+        text
+    }
+
+    /// Whether `typed` is one of the `accepted` answers once both are tidied up.
+    pub fn matches(&self, typed: &str, accepted: &[String]) -> bool
+    {
+        let typed = self.apply(typed);
+        accepted.iter().any(|answer| self.apply(answer) == typed)
+    }
+}
+
+/// The answer to a numeric quiz, right when the typed number is close enough to `value`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct NumericAnswer
+{
+    pub value: f64,
+    /// Largest difference allowed, in `unit`.
+    #[serde(default)]
+    pub absolute: f64,
+    /// Largest difference allowed as a share of `value`, `0.01` is one percent.
+    #[serde(default)]
+    pub relative: f64,
+    /// Unit of `value`, a number typed without a unit is read in it.
+    #[serde(default)]
+    pub unit: String,
+    /// Other units the answer may be typed in and how many `unit`s one of them is worth.
+    #[serde(default)]
+    pub units: Vec<(String, f64)>
+}
+
+impl NumericAnswer
+{
+    /// Splits an answer like `9.8 м/с²` into the number and its unit, without tolerance or other units.
+    pub fn parse(text: &str) -> Option<Self>
+    {
+        let (value, unit) = parse_number(text)?;
+        Some(NumericAnswer { value, unit: unit.to_string(), ..Default::default() })
+    }
+
+    /// Reads `0.1` as an absolute tolerance and `5%` as a relative one.
+    pub fn parse_tolerance(&mut self, text: &str) -> Option<()>
+    {
+        match text.trim().strip_suffix('%')
+        {
+            Some(percent) => self.relative = parse_number(percent).filter(|(_, rest)| rest.is_empty())?.0 / 100.,
+            None => self.absolute = parse_number(text).filter(|(_, rest)| rest.is_empty())?.0
+        }
+
+        Some(())
+    }
+
+    /// Reads other units separated by semicolons, like `км = 1000; см = 0,01`.
+    pub fn parse_units(&mut self, text: &str) -> Option<()>
+    {
+        for unit in text.split(';').map(str::trim).filter(|unit| !unit.is_empty())
+        {
+            let (name, factor) = unit.split_once('=')?;
+            let factor = parse_number(factor).filter(|(_, rest)| rest.is_empty())?.0;
+            self.units.push((name.trim().to_string(), factor));
+        }
+
+        Some(())
+    }
+
+    /// The typed number in `unit`, or `None` if it is not a number or has a unit the quiz does not know.
+    pub fn read(&self, typed: &str) -> Option<f64>
+    {
+        let (number, unit) = parse_number(typed)?;
+        let unit = without_spaces(unit);
+        if unit.is_empty() || unit == without_spaces(&self.unit)
+        {
+            return Some(number);
+        }
+
+        self.units
+            .iter()
+            .find(|(name, _)| without_spaces(name) == unit)
+            .map(|(_, factor)| number * factor)
+    }
+
+    pub fn matches(&self, typed: &str) -> bool
+    {
+        let allowed = self.absolute.max(self.relative * self.value.abs());
+
+        // Rounding while reading the decimals should never fail an exact answer
+        let rounding = 1e-9 * self.value.abs().max(1.);
+        self.read(typed).is_some_and(|number| (number - self.value).abs() <= allowed + rounding)
+    }
+}
+
+/// Splits a number off the start of `text`, with a decimal comma or point, an exponent like `3e8`
+/// or a fraction like `3/4`, and returns it with the rest of the text.
+pub fn parse_number(text: &str) -> Option<(f64, &str)>
+{
+    let text = text.trim();
+    let end = text
+        .char_indices()
+        .find(|(_, character)| !(character.is_ascii_digit() || ".,+-−eE/".contains(*character)))
+        .map_or(text.len(), |(index, _)| index);
+
+    // Units may start with `e` or `/`, so the number is the longest start that still reads as one
+    text[..end]
+        .char_indices()
+        .map(|(index, character)| index + character.len_utf8())
+        .rev()
+        .find_map(|end| read_number(&text[..end]).map(|number| (number, text[end..].trim())))
+}
+
+fn read_number(text: &str) -> Option<f64>
+{
+    let text = text.replace(',', ".").replace('−', "-");
+    let number = match text.split_once('/')
+    {
+        Some((numerator, denominator)) =>
+        {
+            let denominator: f64 = denominator.parse().ok()?;
+            (denominator != 0.).then_some(numerator.parse::<f64>().ok()? / denominator)?
+        },
+        None => text.parse().ok()?
+    };
+
+    number.is_finite().then_some(number)
+}
+
+fn without_spaces(text: &str) -> String
+{
+    text.chars().filter(|character| !character.is_whitespace()).collect()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn accepted(answers: &[&str]) -> Vec<String>
+    {
+        answers.iter().map(|answer| answer.to_string()).collect()
+    }
+
+    #[test]
+    fn default_normalization_ignores_case_spaces_and_yo()
+    {
+        let normalization = Normalization::default();
+        assert_eq!(normalization.apply("  Ёжик   в  ТУМАНЕ "), "ежик в тумане");
+        assert!(normalization.matches("родион", &accepted(&["Родион"])));
+        assert!(normalization.matches("  Родион   Романович", &accepted(&["Родион", "Родион Романович"])));
+        assert!(normalization.matches("Ёж", &accepted(&["еж"])));
+    }
+
+    #[test]
+    fn default_normalization_rejects_other_answers()
+    {
+        let normalization = Normalization::default();
+        assert!(!normalization.matches("Родиона", &accepted(&["Родион"])));
+        assert!(!normalization.matches("", &accepted(&["Родион"])));
+        assert!(!normalization.matches("Родион", &[]));
+    }
+
+    #[test]
+    fn exact_normalization_compares_as_typed()
+    {
+        let normalization = Normalization::parse("none").unwrap();
+        assert!(normalization.matches("Ёж", &accepted(&["Ёж"])));
+        assert!(!normalization.matches("ёж", &accepted(&["Ёж"])));
+        assert!(!normalization.matches("Еж", &accepted(&["Ёж"])));
+        assert!(!normalization.matches(" Ёж", &accepted(&["Ёж"])));
+    }
+
+    #[test]
+    fn parses_normalization_rules()
+    {
+        assert_eq!(Normalization::parse("case, whitespace yo"), Some(Normalization::default()));
+        assert_eq!(Normalization::parse("case"), Some(Normalization { ignore_case: true, collapse_whitespace: false, fold_yo: false }));
+        assert_eq!(Normalization::parse(""), Some(Normalization { ignore_case: false, collapse_whitespace: false, fold_yo: false }));
+        assert_eq!(Normalization::parse("case, accents"), None);
+    }
+
+    fn speed() -> NumericAnswer
+    {
+        let mut speed = NumericAnswer::parse("19,6 м/с").unwrap();
+        speed.parse_tolerance("2%").unwrap();
+        speed.parse_units("км/ч = 0,2777778; см/с = 0.01").unwrap();
+        speed
+    }
+
+    #[test]
+    fn parses_numbers_with_the_rest_of_the_text()
+    {
+        assert_eq!(parse_number("12"), Some((12., "")));
+        assert_eq!(parse_number(" -2,5 м "), Some((-2.5, "м")));
+        assert_eq!(parse_number("−3"), Some((-3., "")));
+        assert_eq!(parse_number("+0.5"), Some((0.5, "")));
+        assert_eq!(parse_number("3e8 м/с"), Some((3e8, "м/с")));
+        assert_eq!(parse_number("3/4"), Some((0.75, "")));
+        assert_eq!(parse_number("5 eV"), Some((5., "eV")));
+        assert_eq!(parse_number("5eV"), Some((5., "eV")));
+    }
+
+    #[test]
+    fn rejects_text_that_is_not_a_number()
+    {
+        assert_eq!(parse_number(""), None);
+        assert_eq!(parse_number("   "), None);
+        assert_eq!(parse_number("abc"), None);
+        assert_eq!(parse_number("м/с 5"), None);
+        assert_eq!(parse_number("-"), None);
+
+        // No fraction divides by zero, what is left over reads as a unit
+        assert_eq!(parse_number("1/0"), Some((1., "/0")));
+    }
+
+    #[test]
+    fn parses_answers_tolerances_and_units()
+    {
+        let speed = speed();
+        assert_eq!(speed.value, 19.6);
+        assert_eq!(speed.unit, "м/с");
+        assert_eq!(speed.absolute, 0.);
+        assert!((speed.relative - 0.02).abs() < 1e-12);
+        assert_eq!(speed.units, vec![(String::from("км/ч"), 0.2777778), (String::from("см/с"), 0.01)]);
+
+        let mut answer = NumericAnswer::parse("12").unwrap();
+        assert_eq!(answer.parse_tolerance("0,1"), Some(()));
+        assert_eq!(answer.absolute, 0.1);
+        assert_eq!(answer.parse_tolerance("0.1 м"), None);
+        assert_eq!(answer.parse_tolerance("много%"), None);
+        assert_eq!(answer.parse_units("км"), None);
+        assert_eq!(answer.parse_units("км = тысяча"), None);
+        assert_eq!(NumericAnswer::parse("двенадцать"), None);
+    }
+
+    #[test]
+    fn reads_numbers_in_every_known_unit()
+    {
+        let speed = speed();
+        assert_eq!(speed.read("19.6"), Some(19.6));
+        assert_eq!(speed.read("19,6 м/с"), Some(19.6));
+        assert_eq!(speed.read("19,6 м / с"), Some(19.6));
+        assert_eq!(speed.read("1960см/с"), Some(19.6));
+        assert!(speed.read("70,56 км/ч").is_some_and(|speed| (speed - 19.6).abs() < 1e-5));
+        assert_eq!(speed.read("19.6 m/s"), None);
+        assert_eq!(speed.read("19.6 М/С"), None);
+        assert_eq!(speed.read(""), None);
+        assert_eq!(speed.read("1/0"), None);
+    }
+
+    #[test]
+    fn matches_within_the_tolerance()
+    {
+        let speed = speed();
+        assert!(speed.matches("19.6"));
+        assert!(speed.matches("19,9 м/с"));
+        assert!(speed.matches("98/5"));
+        assert!(speed.matches("70 км/ч"));
+        assert!(!speed.matches("20.1"));
+        assert!(!speed.matches("-19.6"));
+        assert!(!speed.matches("19.6 m/s"));
+        assert!(!speed.matches(""));
+    }
+
+    #[test]
+    fn exact_answers_forgive_only_rounding()
+    {
+        let answer = NumericAnswer::parse("12").unwrap();
+        assert!(answer.matches("12"));
+        assert!(answer.matches("12,00"));
+        assert!(answer.matches("1.2e1"));
+        assert!(answer.matches("24/2"));
+        assert!(!answer.matches("12.001"));
+
+        // 0.1 + 0.2 is not 0.3 in floating point
+        let answer = NumericAnswer { value: 0.1 + 0.2, ..Default::default() };
+        assert!(answer.matches("0.3"));
+        assert!(!answer.matches("0.30001"));
+    }
+}
@@ -6,6 +6,8 @@ use bevy::sprite::TextureAtlas;
 use bevy::text::Font;
 use bevy_asset_loader::asset_collection::AssetCollection;
 
+use crate::campaign::Campaign;
+
 #[derive(AssetCollection, Resource)]
 pub struct MenuAssets
 {    
@@ -37,27 +39,9 @@ pub struct GameAssets
     #[asset(key = "sonic")]
     pub sonic: Handle<TextureAtlas>,
 
-    #[asset(key = "level1")]
-    pub level1: Handle<Image>,
-
-    #[asset(key = "level2")]
-    pub level2: Handle<Image>,
-
-    #[asset(key = "gate_0")]
-    pub gate0: Handle<Image>,
-
-    #[asset(key = "gate_1")]
-    pub gate1: Handle<Image>,
-    
-    #[asset(key = "gate_2")]
-    pub gate2: Handle<Image>,
-
-    #[asset(key = "gate_3")]
-    pub gate3: Handle<Image>,
-
-    #[asset(key = "gate_4")]
-    pub gate4: Handle<Image>,
+    #[asset(key = "star_post")]
+    pub star_post: Handle<Image>,
 
-    #[asset(key = "gate_5")]
-    pub gate5: Handle<Image>,
+    #[asset(key = "campaign")]
+    pub campaign: Handle<Campaign>,
 }
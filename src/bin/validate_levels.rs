@@ -0,0 +1,85 @@
+//! Checks rooms for mistakes without starting the game.
+//!
+//! Usage: `validate_levels [--assets <dir>] [path...]`, paths are relative to the asset folder
+//! and default to the main campaign. Question banks (`*.questions.ron`) are checked on their own.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use puzzle_game::reachability::analyze_reachability;
+use puzzle_game::validate::{read_bank, read_levels, validate_bank, validate_level};
+
+fn main() -> ExitCode
+{
+    let mut asset_root = PathBuf::from("assets");
+    let mut paths = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next()
+    {
+        if arg == "--assets"
+        {
+            asset_root = args.next().map(PathBuf::from).unwrap_or(asset_root);
+        }
+        else
+        {
+            paths.push(arg);
+        }
+    }
+
+    if paths.is_empty()
+    {
+        paths.push(String::from("main.campaign.ron"));
+    }
+
+    let mut failed = false;
+    for path in paths.iter()
+    {
+        if path.ends_with(".questions.ron")
+        {
+            let problems = match read_bank(&asset_root, path)
+            {
+                Ok(bank) => validate_bank(&bank),
+                Err(error) =>
+                {
+                    eprintln!("{error}");
+                    failed = true;
+                    continue;
+                }
+            };
+
+            for problem in problems.iter()
+            {
+                println!("{path}: {problem}");
+            }
+
+            failed |= !problems.is_empty();
+            continue;
+        }
+
+        let levels = match read_levels(&asset_root, path)
+        {
+            Ok(levels) => levels,
+            Err(error) =>
+            {
+                eprintln!("{error}");
+                failed = true;
+                continue;
+            }
+        };
+
+        for (name, level) in levels.iter()
+        {
+            let mut problems = validate_level(level);
+            problems.extend(analyze_reachability(level));
+            for problem in problems.iter()
+            {
+                println!("{name}: {problem}");
+            }
+
+            failed |= !problems.is_empty();
+        }
+    }
+
+    if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS }
+}
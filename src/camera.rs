@@ -0,0 +1,120 @@
+use bevy::ecs::component::Component;
+use bevy::ecs::query::With;
+use bevy::ecs::query::Without;
+use bevy::ecs::system::Query;
+use bevy::ecs::system::Res;
+use bevy::math::Vec2;
+use bevy::time::Time;
+use bevy::transform::components::Transform;
+
+use crate::animations::AnimatableLayer;
+use crate::level::CurrentLevelData;
+use crate::level::RectData;
+use crate::level::VIEWPORT_SIZE;
+use crate::player::Player;
+
+/// Keeps the player on screen in rooms larger than the viewport.
+#[derive(Component, Debug)]
+pub struct FollowCamera
+{
+    /// The player moves freely inside this box around the camera's focus.
+    pub dead_zone: Vec2,
+    /// How far ahead of the player the camera looks in the direction they face.
+    pub look_ahead: f32,
+    /// How fast the look-ahead swings around when the player turns, in units per second.
+    pub look_ahead_speed: f32,
+    pub focus: Vec2,
+    pub offset: f32
+}
+
+impl Default for FollowCamera
+{
+    fn default() -> Self
+    {
+        FollowCamera
+        {
+            dead_zone: Vec2::new(16., 48.),
+            look_ahead: 40.,
+            look_ahead_speed: 80.,
+            focus: Vec2::ZERO,
+            offset: 0.
+        }
+    }
+}
+
+/// Moves `center` as little as needed to keep the viewport inside `bounds`,
+/// bounds smaller than the viewport are centered instead.
+pub fn clamp_to_bounds(center: Vec2, bounds: &RectData) -> Vec2
+{
+    let half = VIEWPORT_SIZE / 2.;
+    let (min, max) = (bounds.min() + half, bounds.max() - half);
+    let clamp = |value: f32, min: f32, max: f32, middle: f32| if min > max { middle } else { value.clamp(min, max) };
+    Vec2::new(clamp(center.x, min.x, max.x, bounds.x), clamp(center.y, min.y, max.y, bounds.y))
+}
+
+/// Jumps to the player start, so a room never opens with the camera sliding across it.
+pub fn reset_camera(
+    current_level: CurrentLevelData,
+    mut cameras: Query<(&mut Transform, &mut FollowCamera)>
+) {
+    let (center, bounds) = match current_level.get()
+    {
+        Some(level) => (current_level.player_start(), level.camera_bounds()),
+        None => (Vec2::ZERO, RectData::centered(Vec2::ZERO, VIEWPORT_SIZE))
+    };
+
+    for (mut transform, mut camera) in cameras.iter_mut()
+    {
+        camera.focus = center;
+        camera.offset = 0.;
+
+        let position = clamp_to_bounds(center, &bounds).round();
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
+
+/// Menus are drawn around the origin.
+pub fn center_camera(mut cameras: Query<&mut Transform, With<FollowCamera>>)
+{
+    for mut transform in cameras.iter_mut()
+    {
+        transform.translation.x = 0.;
+        transform.translation.y = 0.;
+    }
+}
+
+pub fn follow_player(
+    time: Res<Time>,
+    current_level: CurrentLevelData,
+    players: Query<(&Transform, Option<&AnimatableLayer>), With<Player>>,
+    mut cameras: Query<(&mut Transform, &mut FollowCamera), Without<Player>>
+) {
+    let Ok((player, animatable)) = players.get_single() else
+    {
+        return;
+    };
+
+    let bounds = current_level.get()
+        .map(|level| level.camera_bounds())
+        .unwrap_or(RectData::centered(Vec2::ZERO, VIEWPORT_SIZE));
+
+    let position = player.translation.truncate();
+    let facing = if animatable.is_some_and(|animatable| animatable.flip_x) { -1. } else { 1. };
+
+    for (mut transform, mut camera) in cameras.iter_mut()
+    {
+        let half = camera.dead_zone / 2.;
+        let focus = camera.focus;
+        camera.focus = focus + (position - focus) - (position - focus).clamp(-half, half);
+
+        let target = facing * camera.look_ahead;
+        let step = camera.look_ahead_speed * time.delta_seconds();
+        camera.offset += (target - camera.offset).clamp(-step, step);
+
+        // Whole pixels keep the pixel art from shimmering
+        let center = clamp_to_bounds(camera.focus + Vec2::X * camera.offset, &bounds).round();
+        transform.translation.x = center.x;
+        transform.translation.y = center.y;
+    }
+}
@@ -0,0 +1,116 @@
+use bevy::asset::io::Reader;
+use bevy::asset::Asset;
+use bevy::asset::AssetLoader;
+use bevy::asset::AsyncReadExt;
+use bevy::asset::Handle;
+use bevy::asset::LoadContext;
+use bevy::reflect::TypePath;
+use bevy::utils::BoxedFuture;
+use serde::Deserialize;
+
+use crate::level::LevelData;
+use crate::level::LevelLoaderError;
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct WorldData
+{
+    #[serde(default)]
+    pub name: String,
+    pub levels: Vec<String>
+}
+
+/// Ordered list of rooms, loaded from a `*.campaign.ron` file.
+/// `CurrentLevel(n)` refers to the n-th room counting through all worlds in order.
+#[derive(Asset, TypePath, Debug, Default, Deserialize)]
+pub struct Campaign
+{
+    pub worlds: Vec<WorldData>,
+
+    #[serde(skip)]
+    pub levels: Vec<Handle<LevelData>>
+}
+
+impl Campaign
+{
+    pub fn parse(bytes: &[u8]) -> Result<Self, LevelLoaderError>
+    {
+        Ok(ron::de::from_bytes::<Campaign>(bytes)?)
+    }
+
+    /// Paths of all rooms, counting through all worlds in order.
+    pub fn level_paths(&self) -> impl Iterator<Item = &String>
+    {
+        self.worlds.iter().flat_map(|world| world.levels.iter())
+    }
+
+    pub fn level(&self, number: usize) -> Option<&Handle<LevelData>>
+    {
+        let index = number.checked_sub(1)?;
+        self.levels.get(index)
+    }
+
+    pub fn has_level(&self, number: usize) -> bool
+    {
+        self.level(number).is_some()
+    }
+
+    /// The room after `number`, if the campaign goes on.
+    pub fn next_level(&self, number: usize) -> Option<usize>
+    {
+        let next = number.checked_add(1)?;
+        self.has_level(next).then_some(next)
+    }
+
+    pub fn world_of(&self, number: usize) -> Option<&WorldData>
+    {
+        let mut remaining = number.checked_sub(1)?;
+        for world in self.worlds.iter()
+        {
+            if remaining < world.levels.len()
+            {
+                return Some(world);
+            }
+
+            remaining -= world.levels.len();
+        }
+
+        None
+    }
+}
+
+#[derive(Default)]
+pub struct CampaignLoader;
+
+impl AssetLoader for CampaignLoader
+{
+    type Asset = Campaign;
+    type Settings = ();
+    type Error = LevelLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>>
+    {
+        Box::pin(async move
+        {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+
+            let mut campaign = Campaign::parse(&bytes)?;
+            campaign.levels = campaign
+                .level_paths()
+                .map(|path| load_context.load(path.clone()))
+                .collect();
+
+            Ok(campaign)
+        })
+    }
+
+    fn extensions(&self) -> &[&str]
+    {
+        &["campaign.ron"]
+    }
+}
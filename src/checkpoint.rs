@@ -0,0 +1,144 @@
+use bevy::asset::Handle;
+use bevy::ecs::component::Component;
+use bevy::ecs::schedule::NextState;
+use bevy::ecs::system::Commands;
+use bevy::ecs::system::Query;
+use bevy::ecs::system::Res;
+use bevy::ecs::system::ResMut;
+use bevy::ecs::system::Resource;
+use bevy::math::Vec2;
+use bevy::render::color::Color;
+use bevy::render::texture::Image;
+use bevy::sprite::Sprite;
+use bevy::sprite::SpriteBundle;
+use bevy::transform::components::Transform;
+use bevy_xpbd_2d::components::Collider;
+use bevy_xpbd_2d::components::CollidingEntities;
+use bevy_xpbd_2d::components::CollisionLayers;
+use bevy_xpbd_2d::components::RigidBody;
+use bevy_xpbd_2d::components::Sensor;
+
+use crate::leaderboard::LevelStats;
+use crate::level::CurrentLevelData;
+use crate::level::PointData;
+use crate::locks::Gate;
+use crate::locks::Unlocks;
+use crate::player::PLAYER_SIZE;
+use crate::player::Layer;
+use crate::system::CurrentLevel;
+use crate::system::GameState;
+
+/// Size of `star_post.png`, checkpoints are placed by their center.
+pub const STAR_POST_SIZE: Vec2 = Vec2::new(16., 64.);
+
+/// Tint of a star post the player has touched.
+pub const ACTIVE_STAR_POST_COLOR: Color = Color::rgb(1.0, 0.55, 0.55);
+
+/// The last star post the player touched. Dying in the same room rebuilds it from here.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct Checkpoint
+{
+    pub level: usize,
+    pub post: usize,
+    pub position: Vec2,
+    /// The run timer when the post was touched.
+    pub time: f32,
+    pub opened_gates: Vec<String>,
+    pub unlocks: Unlocks
+}
+
+#[derive(Component, Debug)]
+pub struct StarPost(pub usize);
+
+pub fn touch_star_posts(
+    mut commands: Commands,
+    current_level: CurrentLevelData,
+    game_stats: Res<CurrentLevel>,
+    mut posts: Query<(&StarPost, &Transform, &CollidingEntities, &mut Sprite)>,
+    gates: Query<&Gate>,
+    unlocks: Res<Unlocks>,
+    stats: Res<LevelStats>
+) {
+    let Some(level) = current_level.get() else
+    {
+        return;
+    };
+
+    let active = current_level.checkpoint().map(|checkpoint| checkpoint.post);
+    let mut touched = None;
+    for (post, transform, colliding_entities, _) in posts.iter()
+    {
+        if colliding_entities.0.is_empty() || active == Some(post.0)
+        {
+            continue;
+        }
+
+        // Gates that are gone have been unlocked
+        let opened_gates = level.gates
+            .iter()
+            .filter(|gate| !gates.iter().any(|Gate(id)| *id == gate.id))
+            .map(|gate| gate.id.clone())
+            .collect();
+
+        let base = transform.translation.truncate() - Vec2::Y * STAR_POST_SIZE.y / 2.;
+        commands.insert_resource(Checkpoint
+        {
+            level: game_stats.0,
+            post: post.0,
+            position: base + Vec2::Y * PLAYER_SIZE.y / 2.,
+            time: stats.time,
+            opened_gates,
+            unlocks: unlocks.clone()
+        });
+
+        touched = Some(post.0);
+    }
+
+    // Only the post touched last stays tinted
+    if let Some(touched) = touched
+    {
+        for (post, _, _, mut sprite) in posts.iter_mut()
+        {
+            sprite.color = if post.0 == touched { ACTIVE_STAR_POST_COLOR } else { Color::WHITE };
+        }
+    }
+}
+
+/// Passing through this state despawns the room, so it can be rebuilt at the checkpoint.
+pub fn respawn(mut next_game_state: ResMut<NextState<GameState>>)
+{
+    next_game_state.set(GameState::InGame);
+}
+
+pub fn forget_checkpoint(mut commands: Commands)
+{
+    commands.remove_resource::<Checkpoint>();
+}
+
+/// The post the player touched last is tinted.
+pub fn spawn_star_posts(commands: &mut Commands, texture: &Handle<Image>, posts: &[PointData], checkpoint: Option<&Checkpoint>)
+{
+    for (index, post) in posts.iter().enumerate()
+    {
+        let active = checkpoint.is_some_and(|checkpoint| checkpoint.post == index);
+        commands.spawn((
+            SpriteBundle
+            {
+                texture: texture.clone(),
+                sprite: Sprite
+                {
+                    color: if active { ACTIVE_STAR_POST_COLOR } else { Color::WHITE },
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(post.x, post.y, 0.),
+                ..Default::default()
+            },
+            RigidBody::Static,
+            Collider::cuboid(STAR_POST_SIZE.x, STAR_POST_SIZE.y),
+            Sensor,
+            CollisionLayers::new([Layer::Interactable], [Layer::Player]),
+            StarPost(index),
+            GameState::InGame
+        ));
+    }
+}
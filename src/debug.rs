@@ -0,0 +1,202 @@
+use bevy::ecs::component::Component;
+use bevy::ecs::entity::Entity;
+use bevy::ecs::query::Added;
+use bevy::ecs::query::Or;
+use bevy::ecs::query::With;
+use bevy::ecs::query::Without;
+use bevy::ecs::system::Commands;
+use bevy::ecs::system::Query;
+use bevy::ecs::system::Res;
+use bevy::ecs::system::ResMut;
+use bevy::ecs::system::Resource;
+use bevy::gizmos::gizmos::Gizmos;
+use bevy::hierarchy::DespawnRecursiveExt;
+use bevy::input::keyboard::KeyCode;
+use bevy::input::Input;
+use bevy::math::Vec2;
+use bevy::math::Vec3;
+use bevy::render::color::Color;
+use bevy::text::Text;
+use bevy::text::Text2dBundle;
+use bevy::text::TextStyle;
+use bevy::transform::components::GlobalTransform;
+use bevy::transform::components::Transform;
+use bevy_xpbd_2d::components::CollisionLayers;
+use bevy_xpbd_2d::plugins::debug::DebugRender;
+use bevy_xpbd_2d::plugins::debug::PhysicsDebugConfig;
+use bevy_xpbd_2d::plugins::spatial_query::ShapeHits;
+
+use crate::assets::GameAssets;
+use crate::interactable::GroundObject;
+use crate::interactable::Interactivity;
+use crate::interactable::QuizAction;
+use crate::locks::Gate;
+use crate::player::Layer;
+use crate::player::Player;
+use crate::player::PLAYER_SIZE;
+use crate::system::GameState;
+
+/// Whether colliders, links and grounding are drawn. Toggled with F3.
+#[derive(Resource, Default, PartialEq)]
+pub struct DebugOverlay(pub bool);
+
+/// Text drawn next to an object while the overlay is on.
+#[derive(Component)]
+pub struct DebugLabel;
+
+/// Marks objects that already got their `DebugLabel`.
+#[derive(Component)]
+pub struct DebugLabelled;
+
+pub fn toggle_debug_overlay(
+    keys: Res<Input<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+    mut config: ResMut<PhysicsDebugConfig>
+) {
+    if keys.just_pressed(KeyCode::F3)
+    {
+        overlay.0 = !overlay.0;
+        config.enabled = overlay.0;
+    }
+}
+
+/// Colors every new collider by its main `Layer`.
+pub fn color_debug_colliders(
+    mut commands: Commands,
+    query: Query<(Entity, &CollisionLayers), Added<CollisionLayers>>
+) {
+    for (entity, layers) in query.iter()
+    {
+        let color = if layers.contains_group(Layer::Player) { Color::BLUE }
+            else if layers.contains_group(Layer::Interactable) { Color::CYAN }
+            else if layers.contains_group(Layer::Enemy) { Color::RED }
+            else { Color::GREEN };
+
+        commands.entity(entity).insert(DebugRender { collider_color: Some(color), ..Default::default() });
+    }
+}
+
+/// Hazards, goals and quizzes, anything with a `GroundObject` or an `Interactivity`, that has no label yet.
+type Unlabelled<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static Transform, &'static GameState, Option<&'static GroundObject>, Option<&'static Interactivity>),
+    (Or<(With<GroundObject>, With<Interactivity>)>, Without<DebugLabelled>)
+>;
+
+pub fn update_debug_labels(
+    mut commands: Commands,
+    overlay: Res<DebugOverlay>,
+    game_assets: Res<GameAssets>,
+    unlabelled: Unlabelled,
+    labelled: Query<Entity, With<DebugLabelled>>,
+    labels: Query<Entity, With<DebugLabel>>
+) {
+    if !overlay.0
+    {
+        for label in labels.iter()
+        {
+            commands.entity(label).despawn_recursive();
+        }
+
+        for entity in labelled.iter()
+        {
+            commands.entity(entity).remove::<DebugLabelled>();
+        }
+
+        return;
+    }
+
+    let style = TextStyle
+    {
+        font: game_assets.main_font.clone(),
+        font_size: 8.0,
+        color: Color::WHITE
+    };
+
+    for (entity, transform, state, ground_object, interactivity) in unlabelled.iter()
+    {
+        let text = match (ground_object, interactivity)
+        {
+            (Some(ground_object), _) => format!("-> {:?}", ground_object.next_game_state),
+            (_, Some(interactivity)) if interactivity.actions.is_empty() => String::from("B"),
+            (_, Some(interactivity)) =>
+            {
+                let targets: Vec<String> = interactivity.actions
+                    .iter()
+                    .map(|action| match action
+                    {
+                        QuizAction::Unlock(id) => id.clone(),
+                        QuizAction::Open(id) => format!("{id}~"),
+                        QuizAction::Signal(id) => format!("!{id}"),
+                        QuizAction::Reward { rings, .. } => format!("+{rings}")
+                    })
+                    .collect();
+
+                format!("B -> {}", targets.join(", "))
+            },
+            _ => continue
+        };
+
+        // Colliders are hidden sprites, so the label can not be their child
+        commands.spawn((
+            Text2dBundle
+            {
+                text: Text::from_section(text, style.clone()),
+                transform: Transform::from_translation(transform.translation + Vec3::new(0., 12., 5.)),
+                ..Default::default()
+            },
+            DebugLabel,
+            *state
+        ));
+
+        commands.entity(entity).insert(DebugLabelled);
+    }
+}
+
+/// Draws links from quizzes to their gates and rewards, and whether the player's `ShapeHits` found ground.
+pub fn draw_debug_overlay(
+    mut gizmos: Gizmos,
+    overlay: Res<DebugOverlay>,
+    interactables: Query<(&GlobalTransform, &Interactivity)>,
+    gates: Query<(&GlobalTransform, &Gate)>,
+    transforms: Query<&GlobalTransform>,
+    players: Query<(&GlobalTransform, &ShapeHits), With<Player>>
+) {
+    if !overlay.0
+    {
+        return;
+    }
+
+    for (transform, interactivity) in interactables.iter()
+    {
+        let quiz = transform.translation().truncate();
+        for action in interactivity.actions.iter()
+        {
+            if let QuizAction::Reward { x, y, .. } = action
+            {
+                gizmos.line_2d(quiz, Vec2::new(*x, *y), Color::GOLD);
+            }
+
+            for (gate, _) in gates.iter().filter(|(_, gate)| action.gate() == Some(gate.0.as_str()))
+            {
+                gizmos.line_2d(quiz, gate.translation().truncate(), Color::CYAN);
+            }
+        }
+    }
+
+    for (transform, hits) in players.iter()
+    {
+        let position = transform.translation().truncate();
+        let color = if hits.is_empty() { Color::RED } else { Color::GREEN };
+        gizmos.rect_2d(position, 0., PLAYER_SIZE + Vec2::splat(2.), color);
+
+        let feet = position - Vec2::Y * PLAYER_SIZE.y / 2.;
+        gizmos.circle_2d(feet, 1.5, color);
+
+        for ground in hits.iter().filter_map(|hit| transforms.get(hit.entity).ok())
+        {
+            gizmos.line_2d(feet, ground.translation().truncate(), color);
+        }
+    }
+}
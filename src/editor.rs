@@ -0,0 +1,1446 @@
+use bevy::asset::io::file::FileAssetReader;
+use bevy::asset::AssetPath;
+use bevy::asset::AssetServer;
+use bevy::asset::Assets;
+use bevy::asset::Handle;
+use bevy::ecs::change_detection::DetectChanges;
+use bevy::ecs::change_detection::DetectChangesMut;
+use bevy::ecs::component::Component;
+use bevy::ecs::entity::Entity;
+use bevy::ecs::event::EventReader;
+use bevy::ecs::query::With;
+use bevy::ecs::schedule::NextState;
+use bevy::ecs::schedule::State;
+use bevy::ecs::system::Commands;
+use bevy::ecs::system::Query;
+use bevy::ecs::system::Res;
+use bevy::ecs::system::ResMut;
+use bevy::ecs::system::Resource;
+use bevy::gizmos::gizmos::Gizmos;
+use bevy::hierarchy::BuildChildren;
+use bevy::hierarchy::ChildBuilder;
+use bevy::hierarchy::DespawnRecursiveExt;
+use bevy::input::keyboard::KeyCode;
+use bevy::input::mouse::MouseButton;
+use bevy::input::Input;
+use bevy::log::info;
+use bevy::math::Vec2;
+use bevy::render::camera::Camera;
+use bevy::render::color::Color;
+use bevy::text::TextStyle;
+use bevy::time::Time;
+use bevy::transform::components::GlobalTransform;
+use bevy::transform::components::Transform;
+use bevy::ui::node_bundles::ButtonBundle;
+use bevy::ui::node_bundles::NodeBundle;
+use bevy::ui::node_bundles::TextBundle;
+use bevy::ui::FlexDirection;
+use bevy::ui::Interaction;
+use bevy::ui::PositionType;
+use bevy::ui::Style;
+use bevy::ui::UiRect;
+use bevy::ui::Val;
+use bevy::window::PrimaryWindow;
+use bevy::window::ReceivedCharacter;
+use bevy::window::Window;
+
+use crate::answers::NumericAnswer;
+use crate::assets::GameAssets;
+use crate::camera::clamp_to_bounds;
+use crate::checkpoint::Checkpoint;
+use crate::checkpoint::STAR_POST_SIZE;
+use crate::enemy::EnemyData;
+use crate::enemy::DEFAULT_ENEMY_SPEED;
+use crate::interactable::QuizAction;
+use crate::interactable::QuizButtonData;
+use crate::interactable::QuizData;
+use crate::interactable::QuizKind;
+use crate::interactable::MAX_ANSWERS;
+use crate::interactable::MIN_ANSWERS;
+use crate::interactable::TRUE_FALSE_ANSWERS;
+use crate::interactable::ChangedButtons;
+use crate::level::spawn_scenery;
+use crate::level::CurrentLevelData;
+use crate::level::LevelData;
+use crate::level::PointData;
+use crate::level::RectData;
+use crate::locks::GateData;
+use crate::locks::KeyData;
+use crate::locks::KEY_SIZE;
+use crate::platform::PlatformData;
+use crate::platform::PlatformPath;
+use crate::platform::DEFAULT_PLATFORM_SPEED;
+use crate::player::PLAYER_SIZE;
+use crate::rings::RING_SIZE;
+use crate::spring::SpringData;
+use crate::spring::SpringKind;
+use crate::spring::DEFAULT_SPRING_IMPULSE;
+use crate::switches::SwitchData;
+use crate::switches::SwitchKind;
+use crate::system::GameState;
+use crate::triggers::TriggerAction;
+use crate::triggers::TriggerEvent;
+use crate::validate::validate_level;
+
+/// Width of the side panel, in window pixels. Clicks over it never reach the room.
+const PANEL_WIDTH: f32 = 380.;
+
+/// How fast the arrow keys scroll through large rooms, in units per second.
+const PAN_SPEED: f32 = 160.;
+
+/// How close to the bottom right corner of the selection a click starts resizing it.
+const HANDLE_SIZE: f32 = 4.;
+
+/// Something in the room that can be selected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditorItem
+{
+    PlayerStart,
+    Goal,
+    Ground(usize),
+    Wall(usize),
+    Hazard(usize),
+    Gate(usize),
+    Quiz(usize),
+    Checkpoint(usize),
+    Platform(usize),
+    Enemy(usize),
+    Ring(usize),
+    Spring(usize),
+    Key(usize),
+    Switch(usize),
+    Box(usize)
+}
+
+/// Text of the selection that can be typed into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditorField
+{
+    GateId,
+    KeyId,
+    /// The signal of a switch, or the one a platform waits for.
+    Signal,
+    Question,
+    Answer(usize),
+    /// The number and unit of a numeric answer, like `9.8 м/с²`.
+    NumericValue,
+    /// How far off a numeric answer may be, like `0.1` or `5%`.
+    Tolerance
+}
+
+/// The room being edited and the file it gets saved to.
+#[derive(Resource)]
+pub struct EditorLevel
+{
+    pub level: LevelData,
+    pub handle: Handle<LevelData>,
+    pub path: String
+}
+
+/// Changing this rebuilds the side panel.
+#[derive(Resource, Default)]
+pub struct EditorSelection
+{
+    pub item: Option<EditorItem>,
+    pub field: Option<EditorField>,
+    /// What was typed into a numeric field, the quiz only takes it once it reads as a number.
+    pub draft: String,
+    pub status: String
+}
+
+#[derive(Resource, Default)]
+pub enum EditorDrag
+{
+    #[default]
+    None,
+    Move(Vec2),
+    Resize(Vec2)
+}
+
+#[derive(Component)]
+pub struct EditorPanel;
+
+#[derive(Component, Clone, Copy)]
+pub enum EditorPanelButton
+{
+    Field(EditorField),
+    Correct(usize),
+    NextQuizKind,
+    AddAnswer,
+    RemoveAnswer,
+    NextGate,
+    NextPlatformPath,
+    PlatformTriggered,
+    NextSpringKind,
+    NextSwitchKind,
+    SwitchOn
+}
+
+impl EditorItem
+{
+    fn name(&self) -> &'static str
+    {
+        match self
+        {
+            EditorItem::PlayerStart => "Старт",
+            EditorItem::Goal => "Финиш",
+            EditorItem::Ground(_) => "Земля",
+            EditorItem::Wall(_) => "Стена",
+            EditorItem::Hazard(_) => "Шипы",
+            EditorItem::Gate(_) => "Ворота",
+            EditorItem::Quiz(_) => "Вопрос",
+            EditorItem::Checkpoint(_) => "Чекпоинт",
+            EditorItem::Platform(_) => "Платформа",
+            EditorItem::Enemy(_) => "Враг",
+            EditorItem::Ring(_) => "Кольцо",
+            EditorItem::Spring(_) => "Пружина",
+            EditorItem::Key(_) => "Ключ",
+            EditorItem::Switch(_) => "Переключатель",
+            EditorItem::Box(_) => "Ящик"
+        }
+    }
+
+    /// Things with a fixed size, like the player, can only be moved.
+    fn resizable(&self) -> bool
+    {
+        !matches!(self, EditorItem::PlayerStart | EditorItem::Checkpoint(_) | EditorItem::Ring(_) | EditorItem::Key(_))
+    }
+
+    fn color(&self) -> Color
+    {
+        match self
+        {
+            EditorItem::PlayerStart => Color::BLUE,
+            EditorItem::Goal => Color::YELLOW,
+            EditorItem::Ground(_) => Color::GREEN,
+            EditorItem::Wall(_) => Color::GRAY,
+            EditorItem::Hazard(_) => Color::RED,
+            EditorItem::Gate(_) => Color::ORANGE,
+            EditorItem::Quiz(_) => Color::CYAN,
+            EditorItem::Checkpoint(_) => Color::PURPLE,
+            EditorItem::Platform(_) => Color::OLIVE,
+            EditorItem::Enemy(_) => Color::MAROON,
+            EditorItem::Ring(_) => Color::GOLD,
+            EditorItem::Spring(_) => Color::PINK,
+            EditorItem::Key(_) => Color::TURQUOISE,
+            EditorItem::Switch(_) => Color::LIME_GREEN,
+            EditorItem::Box(_) => Color::BISQUE
+        }
+    }
+}
+
+impl EditorLevel
+{
+    /// Everything that can be selected, the ones drawn last are picked first.
+    fn items(&self) -> Vec<EditorItem>
+    {
+        let level = &self.level;
+        let mut items = Vec::new();
+        items.extend((0..level.ground.len()).map(EditorItem::Ground));
+        items.extend((0..level.walls.len()).map(EditorItem::Wall));
+        items.extend((0..level.platforms.len()).map(EditorItem::Platform));
+        items.push(EditorItem::Goal);
+        items.extend((0..level.hazards.len()).map(EditorItem::Hazard));
+        items.extend((0..level.enemies.len()).map(EditorItem::Enemy));
+        items.extend((0..level.springs.len()).map(EditorItem::Spring));
+        items.extend((0..level.boxes.len()).map(EditorItem::Box));
+        items.extend((0..level.switches.len()).map(EditorItem::Switch));
+        items.extend((0..level.gates.len()).map(EditorItem::Gate));
+        items.extend((0..level.quizzes.len()).map(EditorItem::Quiz));
+        items.extend((0..level.checkpoints.len()).map(EditorItem::Checkpoint));
+        items.extend((0..level.rings.len()).map(EditorItem::Ring));
+        items.extend((0..level.keys.len()).map(EditorItem::Key));
+        items.push(EditorItem::PlayerStart);
+        items
+    }
+
+    fn rect(&self, item: EditorItem) -> Option<RectData>
+    {
+        let level = &self.level;
+        match item
+        {
+            EditorItem::PlayerStart => Some(RectData::centered(Vec2::new(level.player_start.x, level.player_start.y), PLAYER_SIZE)),
+            EditorItem::Goal => Some(level.goal),
+            EditorItem::Ground(index) => level.ground.get(index).copied(),
+            EditorItem::Wall(index) => level.walls.get(index).copied(),
+            EditorItem::Hazard(index) => level.hazards.get(index).copied(),
+            EditorItem::Gate(index) => level.gates.get(index).map(|gate| gate.rect),
+            EditorItem::Quiz(index) => level.quizzes.get(index).map(|quiz| quiz.rect),
+            EditorItem::Checkpoint(index) => level.checkpoints.get(index).map(|post| RectData::centered(Vec2::new(post.x, post.y), STAR_POST_SIZE)),
+            EditorItem::Platform(index) => level.platforms.get(index).map(|platform| platform.rect),
+            EditorItem::Enemy(index) => level.enemies.get(index).map(|enemy| enemy.rect),
+            EditorItem::Ring(index) => level.rings.get(index).map(|ring| RectData::centered(Vec2::new(ring.x, ring.y), RING_SIZE)),
+            EditorItem::Spring(index) => level.springs.get(index).map(|spring| spring.rect),
+            EditorItem::Key(index) => level.keys.get(index).map(|key| RectData::centered(Vec2::new(key.x, key.y), KEY_SIZE)),
+            EditorItem::Switch(index) => level.switches.get(index).map(|switch| switch.rect),
+            EditorItem::Box(index) => level.boxes.get(index).copied()
+        }
+    }
+
+    fn set_rect(&mut self, item: EditorItem, rect: RectData)
+    {
+        let level = &mut self.level;
+        let target = match item
+        {
+            EditorItem::PlayerStart =>
+            {
+                level.player_start.x = rect.x;
+                level.player_start.y = rect.y;
+                return;
+            },
+            EditorItem::Checkpoint(index) | EditorItem::Ring(index) =>
+            {
+                let points = if let EditorItem::Ring(_) = item { &mut level.rings } else { &mut level.checkpoints };
+                if let Some(point) = points.get_mut(index)
+                {
+                    point.x = rect.x;
+                    point.y = rect.y;
+                }
+
+                return;
+            },
+            EditorItem::Key(index) =>
+            {
+                if let Some(key) = level.keys.get_mut(index)
+                {
+                    key.x = rect.x;
+                    key.y = rect.y;
+                }
+
+                return;
+            },
+            EditorItem::Platform(index) =>
+            {
+                // The path moves along with the platform
+                if let Some(platform) = level.platforms.get_mut(index)
+                {
+                    let offset = Vec2::new(rect.x - platform.rect.x, rect.y - platform.rect.y);
+                    for stop in platform.path.iter_mut()
+                    {
+                        stop.x += offset.x;
+                        stop.y += offset.y;
+                    }
+
+                    platform.rect = rect;
+                }
+
+                return;
+            },
+            EditorItem::Goal => Some(&mut level.goal),
+            EditorItem::Ground(index) => level.ground.get_mut(index),
+            EditorItem::Wall(index) => level.walls.get_mut(index),
+            EditorItem::Hazard(index) => level.hazards.get_mut(index),
+            EditorItem::Gate(index) => level.gates.get_mut(index).map(|gate| &mut gate.rect),
+            EditorItem::Quiz(index) => level.quizzes.get_mut(index).map(|quiz| &mut quiz.rect),
+            EditorItem::Enemy(index) => level.enemies.get_mut(index).map(|enemy| &mut enemy.rect),
+            EditorItem::Spring(index) => level.springs.get_mut(index).map(|spring| &mut spring.rect),
+            EditorItem::Switch(index) => level.switches.get_mut(index).map(|switch| &mut switch.rect),
+            EditorItem::Box(index) => level.boxes.get_mut(index)
+        };
+
+        if let Some(target) = target
+        {
+            *target = rect;
+        }
+    }
+
+    fn item_at(&self, position: Vec2) -> Option<EditorItem>
+    {
+        let point = RectData::centered(position, Vec2::splat(0.01));
+        self.items()
+            .into_iter()
+            .rev()
+            .find(|item| self.rect(*item).is_some_and(|rect| rect.overlaps(&point)))
+    }
+
+    fn add(&mut self, key: KeyCode, position: Vec2) -> Option<EditorItem>
+    {
+        let level = &mut self.level;
+        let item = match key
+        {
+            KeyCode::Key1 =>
+            {
+                level.ground.push(RectData::centered(position, Vec2::new(60., 10.)));
+                EditorItem::Ground(level.ground.len() - 1)
+            },
+            KeyCode::Key2 =>
+            {
+                level.walls.push(RectData::centered(position, Vec2::new(10., 60.)));
+                EditorItem::Wall(level.walls.len() - 1)
+            },
+            KeyCode::Key3 =>
+            {
+                level.hazards.push(RectData::centered(position, Vec2::new(15., 15.)));
+                EditorItem::Hazard(level.hazards.len() - 1)
+            },
+            KeyCode::Key4 =>
+            {
+                let id = (level.gates.len()..)
+                    .map(|number| format!("gate_{number}"))
+                    .find(|id| !level.gates.iter().any(|gate| gate.id == *id))
+                    .unwrap_or_default();
+
+                // Borrow the look of an existing gate, the texture can be changed in the file
+                let template = level.gates.first().cloned().unwrap_or_default();
+                level.gates.push(GateData { id, rect: RectData::centered(position, Vec2::new(10., 50.)), ..template });
+                EditorItem::Gate(level.gates.len() - 1)
+            },
+            KeyCode::Key5 =>
+            {
+                let gate = level.gates.first().map(|gate| gate.id.clone());
+                let answers = vec![String::new(); 4];
+                level.quizzes.push(QuizData::from_answers(RectData::centered(position, Vec2::splat(10.)), gate, QuizKind::Single, String::new(), &answers, &[1]));
+                EditorItem::Quiz(level.quizzes.len() - 1)
+            },
+            KeyCode::Key6 =>
+            {
+                level.checkpoints.push(PointData { x: position.x, y: position.y });
+                EditorItem::Checkpoint(level.checkpoints.len() - 1)
+            },
+            KeyCode::Key7 =>
+            {
+                level.platforms.push(PlatformData
+                {
+                    rect: RectData::centered(position, Vec2::new(40., 8.)),
+                    path: vec![PointData { x: position.x + 60., y: position.y }],
+                    speed: DEFAULT_PLATFORM_SPEED,
+                    ..Default::default()
+                });
+
+                EditorItem::Platform(level.platforms.len() - 1)
+            },
+            KeyCode::Key8 =>
+            {
+                level.enemies.push(EnemyData
+                {
+                    rect: RectData::centered(position, Vec2::new(24., 16.)),
+                    speed: DEFAULT_ENEMY_SPEED,
+                    ..Default::default()
+                });
+
+                EditorItem::Enemy(level.enemies.len() - 1)
+            },
+            KeyCode::Key9 =>
+            {
+                level.rings.push(PointData { x: position.x, y: position.y });
+                EditorItem::Ring(level.rings.len() - 1)
+            },
+            KeyCode::Key0 =>
+            {
+                level.springs.push(SpringData
+                {
+                    rect: RectData::centered(position, Vec2::new(16., 8.)),
+                    impulse: PointData { x: DEFAULT_SPRING_IMPULSE.x, y: DEFAULT_SPRING_IMPULSE.y },
+                    ..Default::default()
+                });
+
+                EditorItem::Spring(level.springs.len() - 1)
+            },
+            KeyCode::K =>
+            {
+                let id = (level.keys.len()..)
+                    .map(|number| format!("key_{number}"))
+                    .find(|id| !level.keys.iter().any(|key| key.id == *id))
+                    .unwrap_or_default();
+
+                level.keys.push(KeyData { id, x: position.x, y: position.y, ..Default::default() });
+                EditorItem::Key(level.keys.len() - 1)
+            },
+            KeyCode::L =>
+            {
+                let signal = (level.switches.len()..)
+                    .map(|number| format!("signal_{number}"))
+                    .find(|signal| !level.switches.iter().any(|switch| switch.signal == *signal))
+                    .unwrap_or_default();
+
+                level.switches.push(SwitchData { rect: RectData::centered(position, Vec2::new(12., 12.)), signal, ..Default::default() });
+                EditorItem::Switch(level.switches.len() - 1)
+            },
+            KeyCode::B =>
+            {
+                level.boxes.push(RectData::centered(position, Vec2::splat(16.)));
+                EditorItem::Box(level.boxes.len() - 1)
+            },
+            _ => return None
+        };
+
+        Some(item)
+    }
+
+    /// Quizzes and triggers that named what was removed forget it, the editor has no way to point them elsewhere.
+    fn remove(&mut self, item: EditorItem)
+    {
+        let level = &mut self.level;
+        match item
+        {
+            EditorItem::Ground(index) => { level.ground.remove(index); },
+            EditorItem::Wall(index) => { level.walls.remove(index); },
+            EditorItem::Hazard(index) => { level.hazards.remove(index); },
+            EditorItem::Gate(index) =>
+            {
+                let gate = level.gates.remove(index);
+                if !level.gates.iter().any(|other| other.id == gate.id)
+                {
+                    self.forget_gate(&gate.id);
+                }
+            },
+            EditorItem::Quiz(index) =>
+            {
+                let quiz = level.quizzes.remove(index);
+                if !level.quizzes.iter().any(|other| other.id == quiz.id)
+                {
+                    level.triggers.retain(|trigger| !matches!(&trigger.when, TriggerEvent::Answered { quiz: id, .. } if *id == quiz.id));
+                }
+            },
+            EditorItem::Checkpoint(index) => { level.checkpoints.remove(index); },
+            EditorItem::Platform(index) => { level.platforms.remove(index); },
+            EditorItem::Enemy(index) => { level.enemies.remove(index); },
+            EditorItem::Ring(index) => { level.rings.remove(index); },
+            EditorItem::Spring(index) => { level.springs.remove(index); },
+            EditorItem::Key(index) => { level.keys.remove(index); },
+            EditorItem::Switch(index) => { level.switches.remove(index); },
+            EditorItem::Box(index) => { level.boxes.remove(index); },
+            EditorItem::PlayerStart | EditorItem::Goal => {}
+        }
+    }
+
+    /// Triggers left with nothing to do are removed along with the gate.
+    fn forget_gate(&mut self, id: &str)
+    {
+        for quiz in self.level.quizzes.iter_mut()
+        {
+            if quiz.gate.as_deref() == Some(id)
+            {
+                quiz.gate = None;
+            }
+
+            quiz.actions.retain(|action| action.gate() != Some(id));
+        }
+
+        for trigger in self.level.triggers.iter_mut()
+        {
+            trigger.then.retain(|action| !matches!(action, TriggerAction::OpenGate(gate) if gate == id));
+        }
+
+        self.level.triggers.retain(|trigger| !trigger.then.is_empty());
+    }
+
+    /// Quizzes and triggers follow a gate to its new id, unless another gate still goes by the old one.
+    fn rename_gate(&mut self, index: usize, old: &str)
+    {
+        let Some(new) = self.level.gates.get(index).map(|gate| gate.id.clone()) else
+        {
+            return;
+        };
+
+        if new == old || self.level.gates.iter().any(|gate| gate.id == old)
+        {
+            return;
+        }
+
+        for quiz in self.level.quizzes.iter_mut()
+        {
+            if quiz.gate.as_deref() == Some(old)
+            {
+                quiz.gate = Some(new.clone());
+            }
+
+            for action in quiz.actions.iter_mut()
+            {
+                if let QuizAction::Unlock(id) | QuizAction::Open(id) = action
+                {
+                    if id == old
+                    {
+                        *id = new.clone();
+                    }
+                }
+            }
+        }
+
+        for action in self.level.triggers.iter_mut().flat_map(|trigger| trigger.then.iter_mut())
+        {
+            if let TriggerAction::OpenGate(id) = action
+            {
+                if id == old
+                {
+                    *id = new.clone();
+                }
+            }
+        }
+    }
+
+    fn text_mut(&mut self, item: EditorItem, field: EditorField) -> Option<&mut String>
+    {
+        match (item, field)
+        {
+            (EditorItem::Gate(index), EditorField::GateId) => self.level.gates.get_mut(index).map(|gate| &mut gate.id),
+            (EditorItem::Key(index), EditorField::KeyId) => self.level.keys.get_mut(index).map(|key| &mut key.id),
+            (EditorItem::Switch(index), EditorField::Signal) => self.level.switches.get_mut(index).map(|switch| &mut switch.signal),
+            (EditorItem::Platform(index), EditorField::Signal) => self.level.platforms.get_mut(index).map(|platform| &mut platform.signal),
+            (EditorItem::Quiz(index), EditorField::Question) => self.level.quizzes.get_mut(index).map(|quiz| &mut quiz.question.text),
+            (EditorItem::Quiz(index), EditorField::Answer(answer)) => self.level.quizzes
+                .get_mut(index)
+                .and_then(|quiz| match quiz.kind
+                {
+                    QuizKind::FreeText => quiz.accepted.get_mut(answer),
+                    _ => quiz.buttons.get_mut(answer).map(|button| &mut button.text)
+                }),
+            _ => None
+        }
+    }
+
+    /// Takes a typed numeric field into the quiz, text that is not a number yet is left out.
+    fn set_numeric(&mut self, item: EditorItem, field: EditorField, text: &str)
+    {
+        let EditorItem::Quiz(index) = item else
+        {
+            return;
+        };
+
+        let Some(quiz) = self.level.quizzes.get_mut(index) else
+        {
+            return;
+        };
+
+        match field
+        {
+            EditorField::NumericValue =>
+            {
+                if let Some(typed) = NumericAnswer::parse(text)
+                {
+                    let numeric = quiz.numeric.get_or_insert_with(NumericAnswer::default);
+                    numeric.value = typed.value;
+                    numeric.unit = typed.unit;
+                }
+            },
+            EditorField::Tolerance =>
+            {
+                let mut typed = NumericAnswer::default();
+                if text.trim().is_empty() || typed.parse_tolerance(text).is_some()
+                {
+                    if let Some(numeric) = &mut quiz.numeric
+                    {
+                        numeric.absolute = typed.absolute;
+                        numeric.relative = typed.relative;
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+
+    /// Writes the room next to its source, rooms imported from map editors get a
+    /// `*.level.ron` of their own. A room `validate_level` finds problems with is not written.
+    fn save(&self) -> Result<String, String>
+    {
+        let problems = validate_level(&self.level);
+        if !problems.is_empty()
+        {
+            return Err(problems.join("; "));
+        }
+
+        let text = self.level.to_ron().map_err(|error| error.to_string())?;
+        let file = FileAssetReader::get_base_path().join("assets").join(&self.path);
+        std::fs::write(&file, text).map_err(|error| format!("{}: {error}", file.display()))?;
+        Ok(self.path.clone())
+    }
+}
+
+/// A numeric field as it is typed, empty when the quiz has no number yet.
+fn numeric_text(numeric: Option<&NumericAnswer>, field: EditorField) -> String
+{
+    match (numeric, field)
+    {
+        (Some(numeric), EditorField::NumericValue) => format!("{} {}", numeric.value, numeric.unit).trim_end().to_string(),
+        (Some(numeric), EditorField::Tolerance) if numeric.relative > 0. => format!("{}%", numeric.relative * 100.),
+        (Some(numeric), EditorField::Tolerance) => numeric.absolute.to_string(),
+        _ => String::new()
+    }
+}
+
+fn save_path(path: &AssetPath) -> String
+{
+    let file = path.path().to_string_lossy().replace('\\', "/");
+    match path.label()
+    {
+        None if file.ends_with(".level.ron") => file,
+        label =>
+        {
+            let stem = file.rsplit_once('.').map_or(file.as_str(), |(stem, _)| stem);
+            match label
+            {
+                Some(label) => format!("{stem}.{label}.level.ron"),
+                None => format!("{stem}.level.ron")
+            }
+        }
+    }
+}
+
+pub fn setup_editor(
+    mut commands: Commands,
+    current_level: CurrentLevelData,
+    asset_server: Res<AssetServer>,
+    mut next_game_state: ResMut<NextState<GameState>>
+) {
+    let (Some(level), Some(handle)) = (current_level.get(), current_level.handle()) else
+    {
+        next_game_state.set(GameState::MainMenu);
+        return;
+    };
+
+    let path = asset_server.get_path(handle.id()).map(|path| save_path(&path)).unwrap_or_default();
+    spawn_scenery(&mut commands, level, GameState::Editor);
+
+    commands.insert_resource(EditorLevel { level: level.clone(), handle: handle.clone(), path });
+    commands.insert_resource(EditorSelection::default());
+    commands.insert_resource(EditorDrag::None);
+}
+
+/// Hands the edited room to the game, so playtesting does not need a save.
+pub fn store_editor_level(
+    mut commands: Commands,
+    editor_level: Res<EditorLevel>,
+    mut levels: ResMut<Assets<LevelData>>
+) {
+    levels.insert(editor_level.handle.id(), editor_level.level.clone());
+    commands.remove_resource::<EditorLevel>();
+    commands.remove_resource::<EditorSelection>();
+    commands.remove_resource::<EditorDrag>();
+}
+
+/// F2 switches between playing the current room and editing it.
+pub fn toggle_editor(
+    keys: Res<Input<KeyCode>>,
+    game_state: Res<State<GameState>>,
+    selection: Option<Res<EditorSelection>>,
+    mut next_game_state: ResMut<NextState<GameState>>
+) {
+    if keys.just_pressed(KeyCode::F2)
+    {
+        next_game_state.set(if *game_state.get() == GameState::Editor { GameState::InGame } else { GameState::Editor });
+    }
+    else if keys.just_pressed(KeyCode::Escape) && selection.is_some_and(|selection| selection.field.is_none())
+    {
+        next_game_state.set(GameState::MainMenu);
+    }
+}
+
+pub fn edit_with_mouse(
+    mouse: Res<Input<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut editor_level: ResMut<EditorLevel>,
+    mut selection: ResMut<EditorSelection>,
+    mut drag: ResMut<EditorDrag>
+) {
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.get_single(), cameras.get_single()) else
+    {
+        return;
+    };
+
+    let Some(cursor) = window.cursor_position() else
+    {
+        return;
+    };
+
+    if mouse.just_released(MouseButton::Left) && !matches!(*drag, EditorDrag::None)
+    {
+        // Show where the selection ended up
+        *drag = EditorDrag::None;
+        selection.set_changed();
+    }
+
+    let Some(position) = camera.viewport_to_world_2d(camera_transform, cursor) else
+    {
+        return;
+    };
+
+    if mouse.just_pressed(MouseButton::Left) && cursor.x < window.width() - PANEL_WIDTH
+    {
+        let selected = selection.item.and_then(|item| editor_level.rect(item).map(|rect| (item, rect)));
+        let on_handle = selected.is_some_and(|(item, rect)|
+            item.resizable() && Vec2::new(rect.max().x, rect.min().y).distance(position) < HANDLE_SIZE);
+
+        if let (true, Some((_, rect))) = (on_handle, selected)
+        {
+            *drag = EditorDrag::Resize(Vec2::new(rect.min().x, rect.max().y));
+        }
+        else
+        {
+            let item = editor_level.item_at(position);
+            if item != selection.item
+            {
+                selection.item = item;
+                selection.field = None;
+            }
+
+            *drag = match item.and_then(|item| editor_level.rect(item))
+            {
+                Some(rect) => EditorDrag::Move(Vec2::new(rect.x, rect.y) - position),
+                None => EditorDrag::None
+            };
+        }
+    }
+
+    let Some(item) = selection.item else
+    {
+        return;
+    };
+
+    let Some(rect) = editor_level.rect(item) else
+    {
+        return;
+    };
+
+    // Whole units keep the saved files readable
+    let moved = match *drag
+    {
+        EditorDrag::None => return,
+        EditorDrag::Move(offset) => RectData::centered((position + offset).round(), Vec2::new(rect.width, rect.height)),
+        EditorDrag::Resize(corner) =>
+        {
+            let size = (Vec2::new(position.x - corner.x, corner.y - position.y)).round().max(Vec2::ONE);
+            RectData::centered(corner + Vec2::new(size.x, -size.y) / 2., size)
+        }
+    };
+
+    if moved != rect
+    {
+        editor_level.set_rect(item, moved);
+    }
+}
+
+pub fn edit_with_keyboard(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut editor_level: ResMut<EditorLevel>,
+    mut selection: ResMut<EditorSelection>
+) {
+    // Typing into a field of the side panel
+    if let (Some(item), Some(field)) = (selection.item, selection.field)
+    {
+        let typed: String = characters.read().map(|character| character.char).filter(|character| !character.is_control()).collect();
+        let numeric = matches!(field, EditorField::NumericValue | EditorField::Tolerance);
+        let gate = match (item, field)
+        {
+            (EditorItem::Gate(index), EditorField::GateId) => editor_level.level.gates.get(index).map(|gate| (index, gate.id.clone())),
+            _ => None
+        };
+
+        let text = if numeric { Some(&mut selection.draft) } else { editor_level.text_mut(item, field) };
+        let Some(text) = text else
+        {
+            return;
+        };
+
+        let mut changed = !typed.is_empty();
+        text.push_str(&typed);
+
+        if keys.just_pressed(KeyCode::Back)
+        {
+            changed |= text.pop().is_some();
+        }
+
+        if numeric && changed
+        {
+            let draft = selection.draft.clone();
+            editor_level.set_numeric(item, field, &draft);
+        }
+
+        if let (Some((index, old)), true) = (gate, changed)
+        {
+            editor_level.rename_gate(index, &old);
+        }
+
+        if keys.any_just_pressed([KeyCode::Return, KeyCode::Escape])
+        {
+            selection.field = None;
+        }
+        else if changed
+        {
+            selection.set_changed();
+        }
+
+        return;
+    }
+
+    characters.clear();
+
+    if keys.pressed(KeyCode::ControlLeft) || keys.pressed(KeyCode::ControlRight)
+    {
+        if keys.just_pressed(KeyCode::S)
+        {
+            selection.status = match editor_level.save()
+            {
+                Ok(path) => format!("Сохранено: {path}"),
+                Err(error) => format!("Ошибка: {error}")
+            };
+
+            info!("{}", selection.status);
+        }
+
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Delete)
+    {
+        if let Some(item) = selection.item.take()
+        {
+            // The checkpoint counts levers and star posts by their place in the room
+            if matches!(item, EditorItem::Switch(_) | EditorItem::Checkpoint(_))
+            {
+                commands.remove_resource::<Checkpoint>();
+            }
+
+            editor_level.remove(item);
+        }
+
+        return;
+    }
+
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.get_single(), cameras.get_single()) else
+    {
+        return;
+    };
+
+    let position = window.cursor_position().and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor));
+    for key in keys.get_just_pressed()
+    {
+        if let Some(item) = position.and_then(|position| editor_level.add(*key, position.round()))
+        {
+            selection.item = Some(item);
+            selection.field = None;
+        }
+    }
+}
+
+pub fn pan_editor_camera(
+    keys: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    editor_level: Res<EditorLevel>,
+    selection: Res<EditorSelection>,
+    mut cameras: Query<&mut Transform, With<Camera>>
+) {
+    if selection.field.is_some()
+    {
+        return;
+    }
+
+    let direction = Vec2::new(
+        keys.pressed(KeyCode::Right) as i32 as f32 - keys.pressed(KeyCode::Left) as i32 as f32,
+        keys.pressed(KeyCode::Up) as i32 as f32 - keys.pressed(KeyCode::Down) as i32 as f32
+    );
+
+    for mut transform in cameras.iter_mut()
+    {
+        let center = transform.translation.truncate() + direction * PAN_SPEED * time.delta_seconds();
+        let center = clamp_to_bounds(center, &editor_level.level.camera_bounds()).round();
+        transform.translation.x = center.x;
+        transform.translation.y = center.y;
+    }
+}
+
+pub fn interact_with_editor_panel(
+    interaction_query: Query<(&Interaction, &EditorPanelButton), ChangedButtons>,
+    mut editor_level: ResMut<EditorLevel>,
+    mut selection: ResMut<EditorSelection>
+) {
+    for (interaction, button) in interaction_query.iter()
+    {
+        if *interaction != Interaction::Pressed
+        {
+            continue;
+        }
+
+        if let Some(EditorItem::Platform(index)) = selection.item
+        {
+            if let Some(platform) = editor_level.level.platforms.get_mut(index)
+            {
+                match button
+                {
+                    EditorPanelButton::NextPlatformPath => platform.mode = match platform.mode
+                    {
+                        PlatformPath::Linear => PlatformPath::PingPong,
+                        PlatformPath::PingPong => PlatformPath::Loop,
+                        PlatformPath::Loop => PlatformPath::Linear
+                    },
+                    EditorPanelButton::PlatformTriggered => platform.triggered = !platform.triggered,
+                    EditorPanelButton::Field(field) => selection.field = Some(*field),
+                    _ => continue
+                }
+
+                selection.set_changed();
+            }
+
+            continue;
+        }
+
+        if let (Some(EditorItem::Spring(index)), EditorPanelButton::NextSpringKind) = (selection.item, button)
+        {
+            if let Some(spring) = editor_level.level.springs.get_mut(index)
+            {
+                spring.kind = match spring.kind
+                {
+                    SpringKind::Spring => SpringKind::Bumper,
+                    SpringKind::Bumper => SpringKind::Spring
+                };
+
+                selection.set_changed();
+            }
+
+            continue;
+        }
+
+        if let Some(EditorItem::Switch(index)) = selection.item
+        {
+            if let Some(switch) = editor_level.level.switches.get_mut(index)
+            {
+                match button
+                {
+                    EditorPanelButton::NextSwitchKind => switch.kind = match switch.kind
+                    {
+                        SwitchKind::Lever => SwitchKind::Plate,
+                        SwitchKind::Plate => SwitchKind::Lever
+                    },
+                    EditorPanelButton::SwitchOn => switch.on = !switch.on,
+                    EditorPanelButton::Field(field) => selection.field = Some(*field),
+                    _ => continue
+                }
+
+                selection.set_changed();
+            }
+
+            continue;
+        }
+
+        let Some(EditorItem::Quiz(index)) = selection.item else
+        {
+            if let EditorPanelButton::Field(field) = button
+            {
+                selection.field = Some(*field);
+            }
+
+            continue;
+        };
+
+        let gate_ids: Vec<String> = editor_level.level.gates.iter().map(|gate| gate.id.clone()).collect();
+        let Some(quiz) = editor_level.level.quizzes.get_mut(index) else
+        {
+            continue;
+        };
+
+        match button
+        {
+            EditorPanelButton::Field(field) =>
+            {
+                selection.draft = numeric_text(quiz.numeric.as_ref(), *field);
+                selection.field = Some(*field);
+            },
+            EditorPanelButton::Correct(answer) =>
+            {
+                let multi_select = quiz.kind == QuizKind::MultiSelect;
+                for (button, quiz_button) in quiz.buttons.iter_mut().enumerate()
+                {
+                    if button == *answer
+                    {
+                        quiz_button.is_correct = !multi_select || !quiz_button.is_correct;
+                    }
+                    else if !multi_select
+                    {
+                        quiz_button.is_correct = false;
+                    }
+                }
+
+                selection.set_changed();
+            },
+            EditorPanelButton::NextQuizKind =>
+            {
+                quiz.kind = match quiz.kind
+                {
+                    QuizKind::Single => QuizKind::TrueFalse,
+                    QuizKind::TrueFalse => QuizKind::MultiSelect,
+                    QuizKind::MultiSelect => QuizKind::FreeText,
+                    QuizKind::FreeText => QuizKind::Numeric,
+                    QuizKind::Numeric => QuizKind::Single
+                };
+
+                // The right answers become the accepted spellings, the buttons stay for switching back
+                if quiz.kind == QuizKind::FreeText && quiz.accepted.is_empty()
+                {
+                    quiz.accepted = quiz.buttons
+                        .iter()
+                        .filter(|button| button.is_correct && !button.text.is_empty())
+                        .map(|button| button.text.clone())
+                        .collect();
+                }
+
+                // A numeric question starts from the right answer if it reads as a number
+                if quiz.kind == QuizKind::Numeric && quiz.numeric.is_none()
+                {
+                    quiz.numeric = quiz.accepted.iter().find_map(|accepted| NumericAnswer::parse(accepted));
+                }
+
+                // A true/false question keeps its first two answers, filled in if they are empty
+                if quiz.kind == QuizKind::TrueFalse
+                {
+                    quiz.buttons.resize(2, QuizButtonData::default());
+                    for (button, answer) in quiz.buttons.iter_mut().zip(TRUE_FALSE_ANSWERS)
+                    {
+                        if button.text.is_empty()
+                        {
+                            button.text = answer.to_string();
+                        }
+                    }
+
+                    quiz.layout_answers();
+                }
+
+                // Only multi-select questions keep more than one correct answer
+                if quiz.kind != QuizKind::MultiSelect
+                {
+                    let first = quiz.buttons.iter().position(|button| button.is_correct);
+                    for (index, button) in quiz.buttons.iter_mut().enumerate()
+                    {
+                        button.is_correct = Some(index) == first;
+                    }
+                }
+
+                selection.set_changed();
+            },
+            EditorPanelButton::AddAnswer if quiz.kind == QuizKind::FreeText =>
+            {
+                quiz.accepted.push(String::new());
+                selection.set_changed();
+            },
+            EditorPanelButton::RemoveAnswer if quiz.kind == QuizKind::FreeText && quiz.accepted.len() > 1 =>
+            {
+                quiz.accepted.pop();
+                selection.field = None;
+                selection.set_changed();
+            },
+            EditorPanelButton::AddAnswer if !quiz.kind.is_typed() && quiz.buttons.len() < MAX_ANSWERS =>
+            {
+                quiz.buttons.push(QuizButtonData::default());
+                quiz.layout_answers();
+                selection.set_changed();
+            },
+            EditorPanelButton::RemoveAnswer if !quiz.kind.is_typed() && quiz.buttons.len() > MIN_ANSWERS =>
+            {
+                quiz.buttons.pop();
+                selection.field = None;
+                selection.set_changed();
+            },
+            EditorPanelButton::NextGate =>
+            {
+                let current = quiz.gate.as_ref().and_then(|gate| gate_ids.iter().position(|id| id == gate));
+                quiz.gate = match current
+                {
+                    Some(position) => gate_ids.get(position + 1).cloned(),
+                    None => gate_ids.first().cloned()
+                };
+
+                selection.set_changed();
+            },
+            _ => {}
+        }
+    }
+}
+
+pub fn update_editor_panel(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    editor_level: Res<EditorLevel>,
+    selection: Res<EditorSelection>,
+    panels: Query<Entity, With<EditorPanel>>
+) {
+    if !selection.is_changed()
+    {
+        return;
+    }
+
+    for panel in panels.iter()
+    {
+        commands.entity(panel).despawn_recursive();
+    }
+
+    let text_style = TextStyle
+    {
+        font: game_assets.main_font.clone(),
+        font_size: 22.0,
+        color: Color::BLACK
+    };
+
+    let hint_style = TextStyle { font_size: 18.0, ..text_style.clone() };
+    let level = &editor_level.level;
+
+    let field = |parent: &mut ChildBuilder, button: EditorPanelButton, text: String, active: bool|
+    {
+        parent.spawn((
+            ButtonBundle
+            {
+                style: Style
+                {
+                    width: Val::Percent(100.0),
+                    margin: UiRect::vertical(Val::Px(2.0)),
+                    padding: UiRect::all(Val::Px(4.0)),
+                    ..Default::default()
+                },
+                background_color: if active { Color::rgb(1.0, 0.95, 0.75).into() } else { Color::rgb(0.85, 0.61, 0.38).into() },
+                ..Default::default()
+            },
+            button,
+            GameState::Editor
+        ))
+        .with_children(|parent|
+        {
+            parent.spawn((TextBundle::from_section(text, text_style.clone()), GameState::Editor));
+        });
+    };
+
+    commands.spawn((
+        NodeBundle
+        {
+            style: Style
+            {
+                position_type: PositionType::Absolute,
+                right: Val::Px(0.0),
+                top: Val::Px(0.0),
+                width: Val::Px(PANEL_WIDTH),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(8.0)),
+                ..Default::default()
+            },
+            background_color: Color::rgba(0.977, 0.875, 0.584, 0.9).into(),
+            ..Default::default()
+        },
+        EditorPanel,
+        GameState::Editor
+    ))
+    .with_children(|parent|
+    {
+        let title = match selection.item.and_then(|item| editor_level.rect(item).map(|rect| (item, rect)))
+        {
+            Some((item, rect)) => format!("{} ({}, {}) {}x{}", item.name(), rect.x, rect.y, rect.width, rect.height),
+            None => String::from("Ничего не выбрано")
+        };
+
+        parent.spawn((TextBundle::from_section(title, text_style.clone()), GameState::Editor));
+
+        match selection.item
+        {
+            Some(EditorItem::Gate(index)) =>
+            {
+                let gate = &level.gates[index];
+                field(parent, EditorPanelButton::Field(EditorField::GateId), format!("Имя: {}", gate.id), selection.field == Some(EditorField::GateId));
+            },
+            Some(EditorItem::Key(index)) =>
+            {
+                let key = &level.keys[index];
+                field(parent, EditorPanelButton::Field(EditorField::KeyId), format!("Имя: {}", key.id), selection.field == Some(EditorField::KeyId));
+            },
+            Some(EditorItem::Quiz(index)) =>
+            {
+                let quiz = &level.quizzes[index];
+                field(parent, EditorPanelButton::Field(EditorField::Question), quiz.question.text.clone(), selection.field == Some(EditorField::Question));
+                if let Some(question) = &quiz.from_bank
+                {
+                    // Only the reference is saved, the bank has the final say on the text
+                    let text = format!("Вопрос из банка {}, правки текста не сохранятся", question.bank());
+                    parent.spawn((TextBundle::from_section(text, hint_style.clone()), GameState::Editor));
+                }
+
+                for (answer, accepted) in quiz.accepted.iter().enumerate().filter(|_| quiz.kind == QuizKind::FreeText)
+                {
+                    let active = selection.field == Some(EditorField::Answer(answer));
+                    field(parent, EditorPanelButton::Field(EditorField::Answer(answer)), format!("Принимается: {accepted}"), active);
+                }
+
+                for (answer, button) in quiz.buttons.iter().enumerate().filter(|_| !quiz.kind.is_typed())
+                {
+                    let active = selection.field == Some(EditorField::Answer(answer));
+                    field(parent, EditorPanelButton::Field(EditorField::Answer(answer)), format!("{}. {}", answer + 1, button.text), active);
+                    field(parent, EditorPanelButton::Correct(answer), String::from(if button.is_correct { "[x] верный" } else { "[ ] верный" }), false);
+                }
+
+                if quiz.kind == QuizKind::Numeric
+                {
+                    for (numeric_field, label) in [(EditorField::NumericValue, "Ответ"), (EditorField::Tolerance, "Точность ±")]
+                    {
+                        let active = selection.field == Some(numeric_field);
+                        let text = if active { selection.draft.clone() } else { numeric_text(quiz.numeric.as_ref(), numeric_field) };
+                        field(parent, EditorPanelButton::Field(numeric_field), format!("{label}: {text}"), active);
+                    }
+
+                    // Other units are only edited in the file
+                    let hint = match &quiz.numeric
+                    {
+                        None => Some(String::from("Ответ не задан, введите число")),
+                        Some(numeric) if !numeric.units.is_empty() =>
+                        {
+                            let units: Vec<&str> = numeric.units.iter().map(|(unit, _)| unit.as_str()).collect();
+                            Some(format!("Также в {}, правится в файле", units.join(", ")))
+                        },
+                        Some(_) => None
+                    };
+
+                    if let Some(hint) = hint
+                    {
+                        parent.spawn((TextBundle::from_section(hint, hint_style.clone()), GameState::Editor));
+                    }
+                }
+
+                let kind = match quiz.kind
+                {
+                    QuizKind::Single => "один ответ",
+                    QuizKind::TrueFalse => "верно/неверно",
+                    QuizKind::MultiSelect => "несколько ответов",
+                    QuizKind::FreeText => "ввод текста",
+                    QuizKind::Numeric => "ввод числа"
+                };
+                field(parent, EditorPanelButton::NextQuizKind, format!("Тип: {kind}"), false);
+                if quiz.kind != QuizKind::TrueFalse && quiz.kind != QuizKind::Numeric
+                {
+                    field(parent, EditorPanelButton::AddAnswer, String::from("+ ответ"), false);
+                    field(parent, EditorPanelButton::RemoveAnswer, String::from("- ответ"), false);
+                }
+
+                let gate = quiz.gate.clone().unwrap_or_else(|| String::from("нет"));
+                field(parent, EditorPanelButton::NextGate, format!("Открывает: {gate}"), false);
+                if !quiz.actions.is_empty()
+                {
+                    // Other actions are only edited in the file
+                    let text = format!("И ещё действий: {}", quiz.actions.len());
+                    parent.spawn((TextBundle::from_section(text, hint_style.clone()), GameState::Editor));
+                }
+            },
+            Some(EditorItem::Platform(index)) =>
+            {
+                let platform = &level.platforms[index];
+                let mode = match platform.mode
+                {
+                    PlatformPath::Linear => "в один конец",
+                    PlatformPath::PingPong => "туда и обратно",
+                    PlatformPath::Loop => "по кругу"
+                };
+
+                field(parent, EditorPanelButton::NextPlatformPath, format!("Путь: {mode}"), false);
+                field(parent, EditorPanelButton::PlatformTriggered, String::from(if platform.triggered { "[x] ждёт игрока" } else { "[ ] ждёт игрока" }), false);
+                field(parent, EditorPanelButton::Field(EditorField::Signal), format!("Сигнал: {}", platform.signal), selection.field == Some(EditorField::Signal));
+            },
+            Some(EditorItem::Spring(index)) =>
+            {
+                let spring = &level.springs[index];
+                let kind = match spring.kind
+                {
+                    SpringKind::Spring => "пружина",
+                    SpringKind::Bumper => "бампер"
+                };
+
+                field(parent, EditorPanelButton::NextSpringKind, format!("Вид: {kind}"), false);
+            },
+            Some(EditorItem::Switch(index)) =>
+            {
+                let switch = &level.switches[index];
+                let kind = match switch.kind
+                {
+                    SwitchKind::Lever => "рычаг",
+                    SwitchKind::Plate => "плита"
+                };
+
+                field(parent, EditorPanelButton::NextSwitchKind, format!("Вид: {kind}"), false);
+                field(parent, EditorPanelButton::Field(EditorField::Signal), format!("Сигнал: {}", switch.signal), selection.field == Some(EditorField::Signal));
+                if switch.kind == SwitchKind::Lever
+                {
+                    field(parent, EditorPanelButton::SwitchOn, String::from(if switch.on { "[x] включён" } else { "[ ] включён" }), false);
+                }
+            },
+            _ => {}
+        }
+
+        let hint = "ЛКМ - выбрать и тащить, угол - размер\n\
+            1 земля, 2 стена, 3 шипы, 4 ворота, 5 вопрос, 6 чекпоинт,\n\
+            7 платформа, 8 враг, 9 кольцо, 0 пружина, K ключ,\n\
+            L рычаг, B ящик\n\
+            Delete - удалить, Ctrl+S - сохранить\n\
+            Стрелки - прокрутка, F2 - играть, Esc - в меню";
+
+        parent.spawn((
+            TextBundle::from_section(format!("{hint}\n{}\n{}", editor_level.path, selection.status), hint_style.clone())
+                .with_style(Style { margin: UiRect::top(Val::Px(16.0)), ..Default::default() }),
+            GameState::Editor
+        ));
+    });
+}
+
+pub fn draw_editor_level(
+    mut gizmos: Gizmos,
+    editor_level: Res<EditorLevel>,
+    selection: Res<EditorSelection>
+) {
+    let bounds = editor_level.level.camera_bounds();
+    gizmos.rect_2d(Vec2::new(bounds.x, bounds.y), 0., Vec2::new(bounds.width, bounds.height), Color::WHITE);
+
+    for platform in editor_level.level.platforms.iter()
+    {
+        let stops: Vec<RectData> = platform.stops().collect();
+        for (from, to) in stops.iter().zip(stops.iter().skip(1))
+        {
+            gizmos.line_2d(Vec2::new(from.x, from.y), Vec2::new(to.x, to.y), Color::OLIVE);
+        }
+
+        if platform.mode == PlatformPath::Loop && stops.len() > 2
+        {
+            gizmos.line_2d(Vec2::new(stops[stops.len() - 1].x, stops[stops.len() - 1].y), Vec2::new(stops[0].x, stops[0].y), Color::OLIVE);
+        }
+
+        for stop in stops.iter().skip(1)
+        {
+            gizmos.rect_2d(Vec2::new(stop.x, stop.y), 0., Vec2::new(stop.width, stop.height), Color::rgba(0.5, 0.5, 0., 0.5));
+        }
+    }
+
+    for enemy in editor_level.level.enemies.iter().filter(|enemy| enemy.patrol > 0.)
+    {
+        let center = Vec2::new(enemy.rect.x, enemy.rect.y);
+        gizmos.line_2d(center - Vec2::X * enemy.patrol, center + Vec2::X * enemy.patrol, Color::MAROON);
+    }
+
+    // The impulse is drawn at a tenth of its length
+    for spring in editor_level.level.springs.iter()
+    {
+        let center = Vec2::new(spring.rect.x, spring.rect.y);
+        gizmos.line_2d(center, center + Vec2::new(spring.impulse.x, spring.impulse.y) / 10., Color::PINK);
+    }
+
+    // Triggers are edited in the level file, only their areas are shown
+    for trigger in editor_level.level.triggers.iter()
+    {
+        if let TriggerEvent::Enter(area) = &trigger.when
+        {
+            gizmos.rect_2d(Vec2::new(area.x, area.y), 0., Vec2::new(area.width, area.height), Color::SILVER);
+        }
+    }
+
+    for item in editor_level.items()
+    {
+        let Some(rect) = editor_level.rect(item) else
+        {
+            continue;
+        };
+
+        gizmos.rect_2d(Vec2::new(rect.x, rect.y), 0., Vec2::new(rect.width, rect.height), item.color());
+
+        if selection.item == Some(item)
+        {
+            gizmos.rect_2d(Vec2::new(rect.x, rect.y), 0., Vec2::new(rect.width, rect.height) + 2., Color::WHITE);
+            if item.resizable()
+            {
+                gizmos.rect_2d(Vec2::new(rect.max().x, rect.min().y), 0., Vec2::splat(HANDLE_SIZE), Color::WHITE);
+            }
+        }
+    }
+}
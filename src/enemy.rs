@@ -0,0 +1,245 @@
+use bevy::asset::Handle;
+use bevy::asset::LoadContext;
+use bevy::ecs::component::Component;
+use bevy::ecs::entity::Entity;
+use bevy::ecs::query::With;
+use bevy::ecs::query::Without;
+use bevy::ecs::system::Commands;
+use bevy::ecs::system::Query;
+use bevy::hierarchy::DespawnRecursiveExt;
+use bevy::math::Vec2;
+use bevy::render::color::Color;
+use bevy::sprite::Sprite;
+use bevy::sprite::SpriteBundle;
+use bevy::sprite::SpriteSheetBundle;
+use bevy::sprite::TextureAtlas;
+use bevy::time::Timer;
+use bevy::time::TimerMode;
+use bevy::transform::components::Transform;
+use bevy_xpbd_2d::components::Collider;
+use bevy_xpbd_2d::components::CollidingEntities;
+use bevy_xpbd_2d::components::CollisionLayers;
+use bevy_xpbd_2d::components::LinearVelocity;
+use bevy_xpbd_2d::components::Position;
+use bevy_xpbd_2d::components::RigidBody;
+use bevy_xpbd_2d::plugins::spatial_query::ShapeHits;
+use bevy_xpbd_2d::plugins::spatial_query::SpatialQuery;
+use bevy_xpbd_2d::plugins::spatial_query::SpatialQueryFilter;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::animations::AnimatableLayer;
+use crate::level::RectData;
+use crate::lives::hurt_player;
+use crate::lives::Protected;
+use crate::player::Layer;
+use crate::player::Player;
+use crate::player::JUMP_IMPULSE;
+use crate::player::PLAYER_SIZE;
+use crate::system::GameState;
+
+/// Speed of enemies imported from map editors without a `speed`, in units per second.
+pub const DEFAULT_ENEMY_SPEED: f32 = 30.;
+
+/// Color of enemies without a sprite sheet.
+pub const ENEMY_COLOR: Color = Color::rgb(0.8, 0.2, 0.2);
+
+/// Time each frame of an enemy's walk cycle is shown, in seconds.
+const ENEMY_FRAME_DURATION: f32 = 0.15;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct EnemyData
+{
+    /// Start and collider of the enemy, it walks on whatever is below.
+    pub rect: RectData,
+    /// In units per second.
+    pub speed: f32,
+    /// How far from the start the enemy walks either way, zero to walk until an edge or a wall.
+    #[serde(default)]
+    pub patrol: f32,
+    /// Sprite sheet with the walk cycle in a single row, facing left.
+    #[serde(default)]
+    pub texture: String,
+    #[serde(default)]
+    pub frame_width: f32,
+    #[serde(default)]
+    pub frame_height: f32,
+    #[serde(default)]
+    pub frames: usize,
+
+    #[serde(skip)]
+    pub atlas: Handle<TextureAtlas>
+}
+
+impl EnemyData
+{
+    pub fn load_atlas(&mut self, label: String, load_context: &mut LoadContext)
+    {
+        let atlas = TextureAtlas::from_grid(
+            load_context.load(self.texture.clone()),
+            Vec2::new(self.frame_width, self.frame_height),
+            self.frames.max(1),
+            1,
+            None,
+            None
+        );
+
+        self.atlas = load_context.add_labeled_asset(label, atlas);
+    }
+}
+
+/// How far past its front an enemy looks for ground before walking on.
+const EDGE_LOOKAHEAD: f32 = 2.;
+
+/// How far below its feet ground still counts as ground.
+const EDGE_DEPTH: f32 = 4.;
+
+/// Vertical speed the player bounces off a defeated enemy with.
+const STOMP_BOUNCE: f32 = JUMP_IMPULSE * 0.8;
+
+/// A badnik walking back and forth, turning at ledges, walls and the end of its patrol.
+#[derive(Component, Debug)]
+pub struct Enemy
+{
+    pub speed: f32,
+    /// `1.` walks right, `-1.` walks left.
+    pub direction: f32,
+    pub size: Vec2,
+    /// Patrol limits for the enemy's center, `None` walks until an edge or a wall.
+    pub patrol: Option<(f32, f32)>
+}
+
+impl Enemy
+{
+    pub fn new(enemy: &EnemyData) -> Self
+    {
+        Enemy
+        {
+            speed: enemy.speed,
+            direction: -1.,
+            size: Vec2::new(enemy.rect.width, enemy.rect.height),
+            patrol: (enemy.patrol > 0.).then_some((enemy.rect.x - enemy.patrol, enemy.rect.x + enemy.patrol))
+        }
+    }
+}
+
+pub fn patrol_enemies(
+    spatial_query: SpatialQuery,
+    mut enemies: Query<(&mut Enemy, &Position, &mut LinearVelocity, Option<&mut AnimatableLayer>)>
+) {
+    let filter = || SpatialQueryFilter::new().with_masks([Layer::Ground]);
+    for (mut enemy, position, mut velocity, animatable) in enemies.iter_mut()
+    {
+        let half = enemy.size / 2.;
+        let front = position.x + enemy.direction * (half.x + EDGE_LOOKAHEAD);
+        let feet = position.y - half.y;
+
+        let ledge = spatial_query
+            .cast_ray(Vec2::new(front, feet + 1.), Vec2::NEG_Y, EDGE_DEPTH + 1., true, filter())
+            .is_none();
+
+        let wall = spatial_query
+            .cast_ray(position.0, Vec2::X * enemy.direction, half.x + EDGE_LOOKAHEAD, true, filter())
+            .is_some();
+
+        let patrol_over = enemy.patrol.is_some_and(|(min, max)|
+            (enemy.direction < 0. && position.x <= min) || (enemy.direction > 0. && position.x >= max));
+
+        if ledge || wall || patrol_over
+        {
+            enemy.direction = -enemy.direction;
+        }
+
+        velocity.0 = Vec2::new(enemy.direction * enemy.speed, 0.);
+
+        // Sprite sheets face left
+        if let Some(mut animatable) = animatable
+        {
+            animatable.flip_x = enemy.direction > 0.;
+        }
+    }
+}
+
+/// Players that can land on an enemy.
+type Stompers = (With<Player>, Without<Enemy>);
+
+/// Landing on an enemy from the air defeats it and bounces the player up,
+/// any other contact hurts the player.
+pub fn touch_enemies(
+    mut commands: Commands,
+    enemies: Query<(Entity, &Transform, &CollidingEntities), With<Enemy>>,
+    mut players: Query<(&Transform, &ShapeHits, &mut LinearVelocity), Stompers>,
+    protected: Query<(), Protected>
+) {
+    for (enemy, enemy_transform, colliding_entities) in enemies.iter()
+    {
+        for player in colliding_entities.0.iter()
+        {
+            let Ok((transform, ground_hits, mut velocity)) = players.get_mut(*player) else
+            {
+                continue;
+            };
+
+            let feet = transform.translation.y - PLAYER_SIZE.y / 2.;
+            if ground_hits.is_empty() && feet > enemy_transform.translation.y
+            {
+                velocity.y = STOMP_BOUNCE;
+                commands.entity(enemy).despawn_recursive();
+                break;
+            }
+
+            if !protected.contains(*player)
+            {
+                hurt_player(&mut commands, *player);
+            }
+        }
+    }
+}
+
+/// Also used by triggers that spawn enemies later on.
+pub fn spawn_enemy(commands: &mut Commands, enemy: &EnemyData) -> Entity
+{
+    let transform = Transform::from_xyz(enemy.rect.x, enemy.rect.y, 0.5);
+    let entity = if enemy.texture.is_empty()
+    {
+        commands.spawn(SpriteBundle
+        {
+            sprite: Sprite
+            {
+                color: ENEMY_COLOR,
+                custom_size: Some(Vec2::new(enemy.rect.width, enemy.rect.height)),
+                ..Default::default()
+            },
+            transform,
+            ..Default::default()
+        }).id()
+    }
+    else
+    {
+        commands.spawn((
+            SpriteSheetBundle
+            {
+                texture_atlas: enemy.atlas.clone(),
+                transform,
+                ..Default::default()
+            },
+            AnimatableLayer
+            {
+                timer: Timer::from_seconds(ENEMY_FRAME_DURATION, TimerMode::Repeating),
+                animations: vec![(0, enemy.frames.max(1) - 1)],
+                current_animation: 0,
+                next_animation: 0,
+                flip_x: false,
+                repeat: true
+            }
+        )).id()
+    };
+
+    commands.entity(entity).insert((
+        RigidBody::Kinematic,
+        Collider::cuboid(enemy.rect.width, enemy.rect.height),
+        CollisionLayers::new([Layer::Enemy], [Layer::Player]),
+        Enemy::new(enemy),
+        GameState::InGame
+    )).id()
+}
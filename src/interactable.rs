@@ -1,8 +1,14 @@
 
+use bevy_xpbd_2d::components::CollisionLayers;
+use bevy_xpbd_2d::components::Sensor;
+use leafwing_input_manager::input_map::InputMap;
+use leafwing_input_manager::InputManagerBundle;
 use std::ops::Deref;
 use bevy::app::AppExit;
+use bevy::ecs::change_detection::DetectChanges;
 use bevy::ecs::component::Component;
 use bevy::ecs::entity::Entity;
+use bevy::ecs::event::EventReader;
 use bevy::ecs::event::EventWriter;
 use bevy::ecs::query::Changed;
 use bevy::ecs::query::Or;
@@ -12,30 +18,60 @@ use bevy::ecs::system::Commands;
 use bevy::ecs::system::Query;
 use bevy::ecs::system::Res;
 use bevy::ecs::system::ResMut;
+use bevy::ecs::system::SystemParam;
 use bevy::hierarchy::BuildChildren;
+use bevy::hierarchy::Children;
 use bevy::hierarchy::DespawnRecursiveExt;
+use bevy::input::keyboard::KeyCode;
+use bevy::input::Input;
+use bevy::math::Vec2;
 use bevy::prelude::Deref;
 use bevy::prelude::DerefMut;
 use bevy::render::color::Color;
+use bevy::text::Text;
 use bevy::text::TextAlignment;
 use bevy::text::TextStyle;
 use bevy::ui::node_bundles::ButtonBundle;
+use bevy::ui::node_bundles::NodeBundle;
 use bevy::ui::node_bundles::TextBundle;
 use bevy::ui::widget::Button;
+use bevy::ui::BackgroundColor;
 use bevy::ui::Interaction;
 use bevy::ui::JustifySelf;
 use bevy::ui::PositionType;
 use bevy::ui::Style;
 use bevy::ui::Val;
+use bevy::window::ReceivedCharacter;
 use bevy_xpbd_2d::components::CollidingEntities;
 use leafwing_input_manager::action_state::ActionState;
+use serde::Deserialize;
+use serde::Serialize;
 
+use crate::answers::Normalization;
+use crate::answers::NumericAnswer;
 use crate::assets::GameAssets;
+use crate::leaderboard::LevelStats;
+use crate::level::CurrentLevelData;
+use crate::level::RectData;
+use crate::level::spawn_collider;
+use crate::lives::hurt_player;
+use crate::lives::LifeBonus;
+use crate::lives::Lives;
+use crate::lives::Protected;
+use crate::locks::Gate;
+use crate::locks::FadeOnOpen;
+use crate::locks::Unlocks;
 use crate::player::Immobile;
 use crate::player::PlayerAction;
-use crate::system::CurrentLevel;
+use crate::player::Layer;
+use crate::questions::QuestionRef;
+use crate::rings::spawn_ring_row;
+use crate::shuffle::QuizRng;
 use crate::system::GameState;
 use crate::system::QuizClear;
+use crate::triggers::DEFAULT_MESSAGE_SECONDS;
+use crate::triggers::spawn_message;
+use crate::triggers::QuizAnswered;
 
 #[derive(Component)]
 pub enum MenuButtonAction
@@ -58,11 +94,20 @@ pub struct Interactivity
     pub can_interact: bool,
     pub is_interacting: bool,
     pub question: QuestionData,
-    pub buttons: [QuizButtonData; 4],
-    pub entity: Option<Entity>
+    pub kind: QuizKind,
+    pub buttons: Vec<QuizButtonData>,
+    pub accepted: Vec<String>,
+    pub normalize: Normalization,
+    pub numeric: Option<NumericAnswer>,
+    /// Position in `LevelData::quizzes`, the answers are shuffled by it.
+    pub index: usize,
+    /// `QuizData::id`, triggers refer to quizzes by it.
+    pub id: String,
+    /// What a correct answer does.
+    pub actions: Vec<QuizAction>
 }
 
-#[derive(Clone, Component, Debug, Default, PartialEq)]
+#[derive(Clone, Component, Debug, Default, Deserialize, Serialize, PartialEq)]
 pub struct QuestionData
 {
     pub x: f32,
@@ -70,7 +115,7 @@ pub struct QuestionData
     pub text: String
 }
 
-#[derive(Clone, Component, Debug, Default, PartialEq)]
+#[derive(Clone, Component, Debug, Default, Deserialize, Serialize, PartialEq)]
 pub struct QuizButtonData
 {
     pub x: f32,
@@ -79,6 +124,214 @@ pub struct QuizButtonData
     pub text: String
 }
 
+/// How many answers a quiz may offer.
+pub const MIN_ANSWERS: usize = 2;
+pub const MAX_ANSWERS: usize = 8;
+
+/// Answers of a true/false question that does not name its own.
+pub const TRUE_FALSE_ANSWERS: [&str; 2] = ["Верно", "Неверно"];
+
+/// How a quiz is answered.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub enum QuizKind
+{
+    /// One click on the single correct answer.
+    #[default]
+    Single,
+    TrueFalse,
+    /// Every correct answer is selected, then confirmed.
+    MultiSelect,
+    /// The answer is typed and compared with `QuizData::accepted`.
+    FreeText,
+    /// A number is typed and compared with `QuizData::numeric`.
+    Numeric
+}
+
+impl QuizKind
+{
+    pub fn parse(name: &str) -> Option<Self>
+    {
+        match name.to_lowercase().replace(['_', '-', ' '], "").as_str()
+        {
+            "single" | "choice" => Some(QuizKind::Single),
+            "truefalse" | "bool" => Some(QuizKind::TrueFalse),
+            "multiselect" | "multiple" | "multi" => Some(QuizKind::MultiSelect),
+            "freetext" | "text" | "typed" => Some(QuizKind::FreeText),
+            "numeric" | "number" => Some(QuizKind::Numeric),
+            _ => None
+        }
+    }
+
+    /// Whether the answer is typed into a field instead of picked from buttons.
+    pub fn is_typed(self) -> bool
+    {
+        matches!(self, QuizKind::FreeText | QuizKind::Numeric)
+    }
+}
+
+/// Something a correctly answered quiz does to the room.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub enum QuizAction
+{
+    /// Counts as the quiz for the gate's lock, the gate vanishes once the lock opens.
+    Unlock(String),
+    /// Like `Unlock`, but the gate fades away.
+    Open(String),
+    /// Sends the signal for the rest of the room: platforms waiting for it start
+    /// and spikes listening to it retract.
+    Signal(String),
+    /// Rings laid out in a row centered on the spot.
+    Reward { x: f32, y: f32, rings: u32 }
+}
+
+impl QuizAction
+{
+    /// The `actions` of a quiz object, like `open:door; signal:lift; reward:3@96,40`.
+    /// Reward spots are left in the map's own units.
+    pub fn parse_list(text: &str) -> Option<Vec<Self>>
+    {
+        text
+            .split(';')
+            .map(str::trim)
+            .filter(|action| !action.is_empty())
+            .map(|action| match action.split_once(':').map(|(kind, target)| (kind.trim(), target.trim()))
+            {
+                Some((_, "")) => None,
+                Some(("unlock", id)) => Some(QuizAction::Unlock(id.to_string())),
+                Some(("open", id)) => Some(QuizAction::Open(id.to_string())),
+                Some(("signal", id)) => Some(QuizAction::Signal(id.to_string())),
+                Some(("reward", reward)) =>
+                {
+                    let (rings, spot) = reward.split_once('@')?;
+                    let (x, y) = spot.split_once(',')?;
+                    Some(QuizAction::Reward { x: x.trim().parse().ok()?, y: y.trim().parse().ok()?, rings: rings.trim().parse().ok()? })
+                },
+                _ => None
+            })
+            .collect()
+    }
+
+    /// The gate the action opens, if any.
+    pub fn gate(&self) -> Option<&str>
+    {
+        match self
+        {
+            QuizAction::Unlock(id) | QuizAction::Open(id) => Some(id),
+            _ => None
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct QuizData
+{
+    /// Triggers wait for the quiz by it, it may stay empty if none does.
+    #[serde(default)]
+    pub id: String,
+    pub rect: RectData,
+    /// Short for a single `QuizAction::Unlock`.
+    #[serde(default)]
+    pub gate: Option<String>,
+    #[serde(default)]
+    pub actions: Vec<QuizAction>,
+    /// Takes the question and answers from a bank when the room loads.
+    #[serde(default)]
+    pub from_bank: Option<QuestionRef>,
+    #[serde(default)]
+    pub kind: QuizKind,
+    #[serde(default)]
+    pub question: QuestionData,
+    #[serde(default)]
+    pub buttons: Vec<QuizButtonData>,
+    /// Every way of writing the answer to a free-text quiz.
+    #[serde(default)]
+    pub accepted: Vec<String>,
+    #[serde(default)]
+    pub normalize: Normalization,
+    #[serde(default)]
+    pub numeric: Option<NumericAnswer>,
+    /// Shown after the quiz is answered.
+    #[serde(default)]
+    pub explanation: String
+}
+
+impl QuizData
+{
+    const QUESTION_SLOT: (f32, f32) = (850., 50.);
+    const ANSWER_COLUMNS: [f32; 2] = [700., 1000.];
+    const FIRST_ROW: f32 = 150.;
+    const ROW_HEIGHT: f32 = 100.;
+
+    /// Lays out the answers two to a row under the question, `correct` lists the right ones counting from 1.
+    /// True/false questions without answers get the usual two, free-text questions accept every answer instead
+    /// and numeric questions read the first one as the number and its unit.
+    pub fn from_answers(rect: RectData, gate: Option<String>, kind: QuizKind, question: String, answers: &[String], correct: &[usize]) -> Self
+    {
+        let answers: Vec<String> = match kind
+        {
+            QuizKind::TrueFalse if answers.is_empty() => TRUE_FALSE_ANSWERS.iter().map(|answer| answer.to_string()).collect(),
+            _ => answers.to_vec()
+        };
+
+        let numeric = answers.first().filter(|_| kind == QuizKind::Numeric).and_then(|answer| NumericAnswer::parse(answer));
+        let (accepted, answers) = match kind
+        {
+            QuizKind::FreeText => (answers, Vec::new()),
+            QuizKind::Numeric => (Vec::new(), Vec::new()),
+            _ => (Vec::new(), answers)
+        };
+
+        let mut quiz = QuizData
+        {
+            id: String::new(),
+            rect,
+            gate,
+            actions: Vec::new(),
+            from_bank: None,
+            kind,
+            question: QuestionData { x: Self::QUESTION_SLOT.0, y: Self::QUESTION_SLOT.1, text: question },
+            buttons: answers
+                .into_iter()
+                .enumerate()
+                .map(|(index, text)| QuizButtonData { x: 0., y: 0., is_correct: correct.contains(&(index + 1)), text })
+                .collect(),
+            accepted,
+            normalize: Normalization::default(),
+            numeric,
+            explanation: String::new()
+        };
+
+        quiz.layout_answers();
+        quiz
+    }
+
+    /// Puts every answer back into its slot, after answers were added or removed.
+    pub fn layout_answers(&mut self)
+    {
+        for (index, button) in self.buttons.iter_mut().enumerate()
+        {
+            (button.x, button.y) = Self::answer_slot(index);
+        }
+    }
+
+    pub fn answer_slot(index: usize) -> (f32, f32)
+    {
+        (Self::ANSWER_COLUMNS[index % 2], Self::FIRST_ROW + (index / 2) as f32 * Self::ROW_HEIGHT)
+    }
+
+    /// Where the confirm button of a multi-select quiz goes, under the last row of answers.
+    pub fn confirm_slot(answers: usize) -> (f32, f32)
+    {
+        (Self::QUESTION_SLOT.0, Self::FIRST_ROW + answers.div_ceil(2) as f32 * Self::ROW_HEIGHT)
+    }
+
+    /// Everything a correct answer does, `gate` first.
+    pub fn all_actions(&self) -> Vec<QuizAction>
+    {
+        self.gate.iter().map(|gate| QuizAction::Unlock(gate.clone())).chain(self.actions.iter().cloned()).collect()
+    }
+}
+
 #[derive(Clone, Component, Debug, Default, PartialEq)]
 pub struct QuizLabel;
 
@@ -86,16 +339,111 @@ pub struct QuizLabel;
 pub struct QuizButton
 {
     pub is_correct: bool,
-    pub entity: Option<Entity>,
+    /// Picked but not yet confirmed, multi-select quizzes only.
+    pub selected: bool,
+    /// The entity holding the quiz's `Interactivity`.
+    pub quiz: Entity,
+    pub interactivity: Interactivity,
+    pub player: Entity
+}
+
+/// Checks the selected answers of a multi-select quiz.
+#[derive(Clone, Component, Debug, PartialEq)]
+pub struct QuizConfirm
+{
+    pub quiz: Entity,
+    pub interactivity: Interactivity,
+    pub player: Entity
+}
+
+/// The field a free-text or numeric answer is typed into.
+#[derive(Clone, Component, Debug, PartialEq)]
+pub struct QuizTextInput
+{
+    pub text: String,
+    pub quiz: Entity,
     pub interactivity: Interactivity,
     pub player: Entity
 }
 
+impl QuizTextInput
+{
+    pub fn is_correct(&self) -> bool
+    {
+        match &self.interactivity.numeric
+        {
+            Some(numeric) if self.interactivity.kind == QuizKind::Numeric => numeric.matches(&self.text),
+            _ => self.interactivity.normalize.matches(&self.text, &self.interactivity.accepted)
+        }
+    }
+}
+
+pub const QUIZ_BUTTON_COLOR: Color = Color::rgb(0.85, 0.61, 0.38);
+pub const QUIZ_SELECTED_COLOR: Color = Color::rgb(0.55, 0.75, 0.35);
+pub const QUIZ_INPUT_COLOR: Color = Color::rgb(0.98, 0.94, 0.85);
+
+/// Everything answering a quiz changes.
+#[derive(SystemParam)]
+pub struct QuizOutcome<'w, 's>
+{
+    commands: Commands<'w, 's>,
+    lives: ResMut<'w, Lives>,
+    stats: ResMut<'w, LevelStats>,
+    unlocks: ResMut<'w, Unlocks>,
+    answers: EventWriter<'w, QuizAnswered>,
+    gates: Query<'w, 's, (Entity, &'static Gate)>,
+    quizzes: Query<'w, 's, &'static mut Interactivity>
+}
+
+impl<'w, 's> QuizOutcome<'w, 's>
+{
+    /// A quiz answered right is done, after a wrong answer the player may try again.
+    fn answer(&mut self, quiz: Entity, interactivity: &Interactivity, player: Entity, is_correct: bool)
+    {
+        self.lives.answer(is_correct);
+        self.stats.answer(is_correct);
+        self.answers.send(QuizAnswered { quiz: interactivity.id.clone(), index: interactivity.index, correct: is_correct });
+        if is_correct
+        {
+            for action in interactivity.actions.iter()
+            {
+                apply_quiz_action(&mut self.commands, &mut self.unlocks, &self.gates, action);
+            }
+        }
+        else
+        {
+            // A hit the rings absorb leaves the player free to move on
+            hurt_player(&mut self.commands, player);
+        }
+
+        if let Ok(mut answered) = self.quizzes.get_mut(quiz)
+        {
+            answered.can_interact = !is_correct;
+            answered.is_interacting = false;
+        }
+
+        self.commands.entity(player).remove::<Immobile>();
+        self.commands.insert_resource(QuizClear);
+    }
+
+    /// Closes the popup without answering, like pressing the interact key again.
+    fn close(&mut self, quiz: Entity, player: Entity)
+    {
+        if let Ok(mut closed) = self.quizzes.get_mut(quiz)
+        {
+            closed.is_interacting = false;
+        }
+
+        self.commands.entity(player).remove::<Immobile>();
+        self.commands.insert_resource(QuizClear);
+    }
+}
+
+/// Buttons that were hovered, pressed or left this frame.
+pub type ChangedButtons = (Changed<Interaction>, With<Button>);
+
 pub fn interact_with_menu_button(
-    interaction_query: Query<
-        (&Interaction, &MenuButtonAction),
-        (Changed<Interaction>, With<Button>),
-    >,
+    interaction_query: Query<(&Interaction, &MenuButtonAction), ChangedButtons>,
     mut game_state: ResMut<NextState<GameState>>,
     mut ev_app_exit: EventWriter<AppExit>
 ) {
@@ -106,7 +454,8 @@ pub fn interact_with_menu_button(
             match menu_button_action
             {
                 MenuButtonAction::Quit => ev_app_exit.send(AppExit),
-                MenuButtonAction::Play => game_state.set(GameState::LevelCompleted),
+                // `LevelCompleted` records a finished run, a new one starts straight in the room
+                MenuButtonAction::Play => game_state.set(GameState::InGame),
                 MenuButtonAction::BackToMenu => game_state.set(GameState::MainMenu)
             }
         }
@@ -114,19 +463,28 @@ pub fn interact_with_menu_button(
 }
 
 pub fn interact_with_gobject(
-    mut game_stats: ResMut<CurrentLevel>,
-    query: Query<(&GroundObject, &CollidingEntities)>,
+    mut commands: Commands,
+    mut lives: ResMut<Lives>,
+    query: Query<(&GroundObject, &CollidingEntities, Option<&LifeBonus>)>,
+    protected: Query<(), Protected>,
     mut game_state: ResMut<NextState<GameState>>
 ) {
-    for (object, colliding_entities) in &query
+    for (object, colliding_entities, life_bonus) in &query
     {
-        if colliding_entities.0.len() > 0
+        if !colliding_entities.0.is_empty()
         {
-            if object.next_game_state != GameState::GameOver
+            // Hazards hurt the player instead of ending the game right away
+            if object.next_game_state == GameState::GameOver
             {
-                game_stats.0 += 1;
+                for player in colliding_entities.0.iter().filter(|player| !protected.contains(**player))
+                {
+                    hurt_player(&mut commands, *player);
+                }
+
+                continue;
             }
-            
+
+            lives.award(life_bonus.map_or(0, |bonus| bonus.0));
             game_state.set(object.next_game_state);
         }
     }
@@ -135,24 +493,33 @@ pub fn interact_with_gobject(
 pub fn update_player_interaction(
     mut commands: Commands,
     game_assets: Res<GameAssets>,
+    mut rng: ResMut<QuizRng>,
+    stats: Res<LevelStats>,
     mut query: Query<(
+        Entity,
         &ActionState<PlayerAction>,
         &mut Interactivity,
         &CollidingEntities
     )>
 ) {
-    for (action_state, mut interactivity, colliding_entities) in query.iter_mut()
+    for (quiz, action_state, mut interactivity, colliding_entities) in query.iter_mut()
     {
-        if interactivity.can_interact && colliding_entities.0.len() > 0 && action_state.just_pressed(PlayerAction::Interact)
+        if interactivity.can_interact && !colliding_entities.0.is_empty() && action_state.just_pressed(PlayerAction::Interact)
         {
             let player_entity = colliding_entities.0.iter().next().unwrap();
             let mut player_commands = commands.entity(*player_entity);
             
             if interactivity.is_interacting
             {
+                // The interact key may be part of a typed answer, Escape closes the popup instead
+                if interactivity.kind.is_typed()
+                {
+                    continue;
+                }
+
                 interactivity.is_interacting = false;
                 player_commands.remove::<Immobile>();
-                commands.insert_resource(QuizClear(true));
+                commands.insert_resource(QuizClear);
             }
             else
             {                
@@ -171,7 +538,7 @@ pub fn update_player_interaction(
                     ..Default::default()
                 };
 
-                let color = Color::rgb(0.85, 0.61, 0.38);
+                let color = QUIZ_BUTTON_COLOR;
 
                 commands.spawn(
                     (
@@ -185,7 +552,7 @@ pub fn update_player_interaction(
                                 position_type: PositionType::Absolute,
                                 ..Default::default()
                             },
-                            background_color: color.clone().into(),
+                            background_color: color.into(),
                             ..Default::default()
                         },
                         QuizLabel,
@@ -203,25 +570,33 @@ pub fn update_player_interaction(
                     }
                 );
 
-                for quiz_button in interactivity.buttons.iter()
+                // The answers trade places every time, true/false questions keep their usual order
+                let mut slots: Vec<(f32, f32)> = interactivity.buttons.iter().map(|quiz_button| (quiz_button.x, quiz_button.y)).collect();
+                if interactivity.kind != QuizKind::TrueFalse
+                {
+                    rng.shuffle(&stats.level, interactivity.index, &mut slots);
+                }
+
+                for (quiz_button, (x, y)) in interactivity.buttons.iter().zip(slots).filter(|_| !interactivity.kind.is_typed())
                 {
                     commands.spawn((
                         ButtonBundle {
                             style: Style
                             {
-                                left: Val::Px(quiz_button.x),
-                                top: Val::Px(quiz_button.y),
+                                left: Val::Px(x),
+                                top: Val::Px(y),
                                 width: Val::Px(250.0),
                                 height: Val::Px(65.0),
                                 position_type: PositionType::Absolute,
                                 ..Default::default()
                             },
-                            background_color: color.clone().into(),
+                            background_color: color.into(),
                             ..Default::default()
                         },
                         QuizButton {
                             is_correct: quiz_button.is_correct,
-                            entity: interactivity.entity,
+                            selected: false,
+                            quiz,
                             interactivity: interactivity.deref().to_owned(),
                             player: *player_entity
                         },
@@ -239,45 +614,244 @@ pub fn update_player_interaction(
                         }
                     );
                 }
+
+                if interactivity.kind.is_typed()
+                {
+                    // As wide as a row of answers
+                    let (x, y) = QuizData::answer_slot(0);
+                    commands.spawn((
+                        NodeBundle {
+                            style: Style
+                            {
+                                left: Val::Px(x),
+                                top: Val::Px(y),
+                                width: Val::Px(550.0),
+                                height: Val::Px(65.0),
+                                position_type: PositionType::Absolute,
+                                ..Default::default()
+                            },
+                            background_color: QUIZ_INPUT_COLOR.into(),
+                            ..Default::default()
+                        },
+                        QuizTextInput {
+                            text: String::new(),
+                            quiz,
+                            interactivity: interactivity.deref().to_owned(),
+                            player: *player_entity
+                        },
+                        GameState::InGame
+                    ))
+                    .with_children(
+                        |parent|
+                        {
+                            parent.spawn((
+                                TextBundle::from_section("_", text_style.clone())
+                                    .with_text_alignment(TextAlignment::Center)
+                                    .with_style(label_style.clone()),
+                                GameState::InGame
+                            ));
+                        }
+                    );
+                }
+
+                if interactivity.kind == QuizKind::MultiSelect || interactivity.kind.is_typed()
+                {
+                    let answers = if interactivity.kind.is_typed() { 1 } else { interactivity.buttons.len() };
+                    let (x, y) = QuizData::confirm_slot(answers);
+                    commands.spawn((
+                        ButtonBundle {
+                            style: Style
+                            {
+                                left: Val::Px(x),
+                                top: Val::Px(y),
+                                width: Val::Px(250.0),
+                                height: Val::Px(65.0),
+                                position_type: PositionType::Absolute,
+                                ..Default::default()
+                            },
+                            background_color: color.into(),
+                            ..Default::default()
+                        },
+                        QuizConfirm {
+                            quiz,
+                            interactivity: interactivity.deref().to_owned(),
+                            player: *player_entity
+                        },
+                        GameState::InGame
+                    ))
+                    .with_children(
+                        |parent|
+                        {
+                            parent.spawn((
+                                TextBundle::from_section("Ответить", text_style.clone())
+                                    .with_text_alignment(TextAlignment::Center)
+                                    .with_style(label_style.clone()),
+                                GameState::InGame
+                            ));
+                        }
+                    );
+                }
             }
         }
     }
 }
 
+/// Answers single-choice quizzes right away, multi-select answers are only toggled.
 pub fn interact_with_quiz_button(
-    mut commands: Commands,
-    mut interaction_query: Query<
-        (&Interaction, &QuizButton),
-        (Changed<Interaction>, With<Button>),
-    >,
-    mut game_state: ResMut<NextState<GameState>>
+    mut interaction_query: Query<(&Interaction, &mut QuizButton, &mut BackgroundColor), ChangedButtons>,
+    mut outcome: QuizOutcome
 ) {
-    for (interaction, quiz_button) in interaction_query.iter_mut()
+    for (interaction, mut quiz_button, mut background_color) in interaction_query.iter_mut()
     {
         if *interaction == Interaction::Pressed
         {
-            if quiz_button.is_correct
+            if quiz_button.interactivity.kind == QuizKind::MultiSelect
             {
-                if let Some(removed) = quiz_button.entity
-                {
-                    commands.entity(removed).despawn_recursive();
-                }
-                
-                commands.entity(quiz_button.player).remove::<Immobile>();
-                commands.insert_resource(QuizClear(false));
+                quiz_button.selected = !quiz_button.selected;
+                *background_color = if quiz_button.selected { QUIZ_SELECTED_COLOR } else { QUIZ_BUTTON_COLOR }.into();
+                continue;
             }
-            else
+
+            outcome.answer(quiz_button.quiz, &quiz_button.interactivity, quiz_button.player, quiz_button.is_correct);
+        }
+    }
+}
+
+/// The selection is right when it holds every correct answer and nothing else,
+/// a typed answer when it is one of the accepted ones.
+pub fn confirm_quiz_selection(
+    interaction_query: Query<(&Interaction, &QuizConfirm), ChangedButtons>,
+    quiz_buttons: Query<&QuizButton>,
+    inputs: Query<&QuizTextInput>,
+    mut outcome: QuizOutcome
+) {
+    for (interaction, confirm) in interaction_query.iter()
+    {
+        if *interaction == Interaction::Pressed
+        {
+            let is_correct = match confirm.interactivity.kind
             {
-                game_state.set(GameState::GameOver);
-                commands.insert_resource(QuizClear(false));
+                QuizKind::FreeText | QuizKind::Numeric => inputs
+                    .iter()
+                    .any(|input| input.quiz == confirm.quiz && input.is_correct()),
+                _ => quiz_buttons
+                    .iter()
+                    .filter(|quiz_button| quiz_button.quiz == confirm.quiz)
+                    .all(|quiz_button| quiz_button.selected == quiz_button.is_correct)
+            };
+
+            outcome.answer(confirm.quiz, &confirm.interactivity, confirm.player, is_correct);
+        }
+    }
+}
+
+/// Keyboard input goes into the text field of the open quiz, Enter answers and Escape closes the popup.
+pub fn type_quiz_answer(
+    keys: Res<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut inputs: Query<(&mut QuizTextInput, &Children)>,
+    quizzes: Query<&Interactivity>,
+    mut texts: Query<&mut Text>,
+    mut outcome: QuizOutcome
+) {
+    let typed: String = characters.read().map(|character| character.char).filter(|character| !character.is_control()).collect();
+    for (mut input, children) in inputs.iter_mut()
+    {
+        if !quizzes.get(input.quiz).is_ok_and(|quiz| quiz.is_interacting)
+        {
+            continue;
+        }
+
+        // The key that opened the popup is not part of the answer
+        if input.is_added()
+        {
+            continue;
+        }
+
+        if keys.just_pressed(KeyCode::Return)
+        {
+            outcome.answer(input.quiz, &input.interactivity, input.player, input.is_correct());
+            continue;
+        }
+
+        if keys.just_pressed(KeyCode::Escape)
+        {
+            outcome.close(input.quiz, input.player);
+            continue;
+        }
+
+        if !typed.is_empty()
+        {
+            input.text.push_str(&typed);
+        }
+
+        if keys.just_pressed(KeyCode::Back)
+        {
+            input.text.pop();
+        }
+
+        if !input.is_changed()
+        {
+            continue;
+        }
+
+        for child in children.iter()
+        {
+            if let Ok(mut text) = texts.get_mut(*child)
+            {
+                text.sections[0].value = format!("{}_", input.text);
             }
         }
     }
 }
 
+/// Shows the explanation a quiz took from its question bank once it is answered.
+pub fn show_explanations(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    current_level: CurrentLevelData,
+    mut answers: EventReader<QuizAnswered>
+) {
+    let Some(level) = current_level.get() else
+    {
+        return;
+    };
+
+    for answer in answers.read()
+    {
+        if let Some(quiz) = level.quizzes.get(answer.index).filter(|quiz| !quiz.explanation.is_empty())
+        {
+            spawn_message(&mut commands, game_assets.main_font.clone(), quiz.explanation.clone(), DEFAULT_MESSAGE_SECONDS);
+        }
+    }
+}
+
+fn apply_quiz_action(commands: &mut Commands, unlocks: &mut Unlocks, gates: &Query<(Entity, &Gate)>, action: &QuizAction)
+{
+    match action
+    {
+        // The gate's lock decides whether this is enough to open it
+        QuizAction::Unlock(id) => { unlocks.quizzes.insert(id.clone()); },
+        QuizAction::Open(id) =>
+        {
+            for (entity, _) in gates.iter().filter(|(_, gate)| gate.0 == *id)
+            {
+                commands.entity(entity).insert(FadeOnOpen);
+            }
+
+            unlocks.quizzes.insert(id.clone());
+        },
+        QuizAction::Signal(id) => { unlocks.sent.insert(id.clone()); },
+        QuizAction::Reward { x, y, rings } => spawn_ring_row(commands, Vec2::new(*x, *y), *rings)
+    }
+}
+
+/// Everything a quiz popup is made of.
+type QuizPopup = Or<(With<QuizButton>, With<QuizLabel>, With<QuizConfirm>, With<QuizTextInput>)>;
+
 pub fn clear_quiz_buttons(
     mut commands: Commands,
-    query: Query<Entity, Or<(With<QuizButton>, With<QuizLabel>)>>
+    query: Query<Entity, QuizPopup>
 ) {
     for quiz_button in query.iter()
     {
@@ -287,14 +861,65 @@ pub fn clear_quiz_buttons(
     commands.remove_resource::<QuizClear>();
 }
 
-pub fn make_uninteractable(
-    game_stats: Res<QuizClear>,
-    mut query: Query<&mut Interactivity>
-) {
-    for mut interactivity in query.iter_mut()
+pub fn spawn_quizzes(commands: &mut Commands, quizzes: &[QuizData])
+{
+    for (index, quiz) in quizzes.iter().enumerate()
+    {
+        let entity = spawn_collider(commands, &quiz.rect, CollisionLayers::new([Layer::Interactable], [Layer::Player]));
+
+        commands.entity(entity).insert((
+            Sensor,
+            InputManagerBundle::<PlayerAction>
+            {
+                action_state: ActionState::default(),
+                input_map: InputMap::new([ (KeyCode::B, PlayerAction::Interact) ]),
+            },
+            Interactivity
+            {
+                can_interact: true,
+                is_interacting: false,
+                question: quiz.question.clone(),
+                index,
+                id: quiz.id.clone(),
+                actions: quiz.all_actions(),
+                kind: quiz.kind,
+                buttons: quiz.buttons.clone(),
+                accepted: quiz.accepted.clone(),
+                normalize: quiz.normalize,
+                numeric: quiz.numeric.clone()
+            }
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn parses_quiz_actions()
+    {
+        assert_eq!(
+            QuizAction::parse_list("open:door; signal:lift; reward:3@96,40; unlock: hatch ;"),
+            Some(vec![
+                QuizAction::Open(String::from("door")),
+                QuizAction::Signal(String::from("lift")),
+                QuizAction::Reward { x: 96., y: 40., rings: 3 },
+                QuizAction::Unlock(String::from("hatch"))
+            ])
+        );
+        assert_eq!(QuizAction::parse_list(""), Some(Vec::new()));
+    }
+
+    #[test]
+    fn rejects_malformed_quiz_actions()
     {
-        interactivity.can_interact = game_stats.0;
-        interactivity.is_interacting = false;
-        break;
+        assert_eq!(QuizAction::parse_list("open:"), None);
+        assert_eq!(QuizAction::parse_list("open:door; explode:door"), None);
+        assert_eq!(QuizAction::parse_list("reward:3"), None);
+        assert_eq!(QuizAction::parse_list("reward:many@96,40"), None);
+        assert_eq!(QuizAction::parse_list("reward:3@96"), None);
+        assert_eq!(QuizAction::parse_list("door"), None);
     }
 }
@@ -0,0 +1,617 @@
+use std::path::Path;
+
+use bevy::asset::io::Reader;
+use bevy::asset::Asset;
+use bevy::asset::AssetLoader;
+use bevy::asset::AsyncReadExt;
+use bevy::asset::LoadContext;
+use bevy::math::Vec2;
+use bevy::reflect::TypePath;
+use bevy::utils::BoxedFuture;
+use bevy::utils::HashMap;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::answers::Normalization;
+use crate::enemy::EnemyData;
+use crate::enemy::DEFAULT_ENEMY_SPEED;
+use crate::interactable::QuizAction;
+use crate::interactable::QuizData;
+use crate::interactable::QuizKind;
+use crate::level::resolve_path;
+use crate::level::LevelData;
+use crate::level::LevelLoaderError;
+use crate::level::PointData;
+use crate::level::RectData;
+use crate::level::TileData;
+use crate::level::TileLayerData;
+use crate::level::TilesetData;
+use crate::locks::GateData;
+use crate::locks::KeyData;
+use crate::locks::LockData;
+use crate::platform::PlatformData;
+use crate::platform::PlatformPath;
+use crate::platform::DEFAULT_PLATFORM_SPEED;
+use crate::questions::load_questions;
+use crate::questions::QuestionRef;
+use crate::spring::SpringData;
+use crate::spring::SpringKind;
+use crate::spring::DEFAULT_SPRING_IMPULSE;
+use crate::switches::SwitchData;
+use crate::switches::SwitchKind;
+use crate::switches::SwitchedHazardData;
+use crate::triggers::TriggerAction;
+use crate::triggers::TriggerData;
+use crate::triggers::TriggerEvent;
+
+/// An LDtk project (`*.ldtk`). Every LDtk level becomes a room labeled with its identifier,
+/// so a campaign can list them one by one as `"levels/world.ldtk#Level_0"`.
+///
+/// Entities are matched by identifier: `Ground`, `Wall`, `Gate`, `Hazard`, `Goal`, `Quiz`,
+/// `Checkpoint`, `Ring`, `Platform`, `Enemy`, `Spring`, `Bumper`, `Key`, `Lever`, `Plate`, `Box`, `Trigger` and `PlayerStart`. Gates take their sprite from the `texture` field, can be named with
+/// an `id` field and read a `lock` like `quiz & key:red | key:master`. Keys read `id` and `texture`. Quizzes can be named with `id` and read `question`, `answers`, `correct` (1-based, an integer or an array), `kind`
+/// (`single`, `true_false`, `multi_select`, `free_text`, where every answer is accepted and `normalize` lists the rules
+/// kept out of `case`, `whitespace` and `yo`, or `numeric`, where the first answer is the number and its unit, `tolerance`
+/// is like `0.1` or `5%` and `units` like `км = 1000; см = 0,01`), `gate`
+/// (an entity reference or a gate id) and `actions` (like `open:door; signal:lift; reward:3@96,40`, spots in level pixels). Platforms read `path` (an array of points for the
+/// centers of the next stops), `speed`, `mode` (`linear`, `ping_pong` or `loop`), `triggered`, `signal` and `texture`.
+/// Enemies read `speed`, `patrol` and a walk cycle from `texture`, `frameWidth`, `frameHeight` and `frames`.
+/// Springs and bumpers read `impulseX`, `impulseY` (in world units, up is positive) and `texture`. Levers and plates read
+/// `signal`, `on` and `texture`, hazards with a `signal` retract while it is sent or only come out then if `inverted`.
+/// Triggers read `when` (`enter` for the entity's own area, the default, `correct:<quiz>`, `wrong:<quiz>`, `signal_on:<signal>`,
+/// `signal_off:<signal>` or `after:<seconds>`, a plain `correct` or `wrong` uses the `quiz` entity reference), `then`
+/// (like `open:door; message:Hello; sound:bell.ogg`, relative to the project) and `repeat`.
+/// Instead of their own text, quizzes can read `bank` (a `*.questions.ron` file relative to the project) and `pick`
+/// (a question id, or `subject:<subject>` optionally followed by `@<difficulty>`).
+/// Tile and auto layers are drawn as the background.
+#[derive(Asset, TypePath, Debug, Default)]
+pub struct LdtkProject;
+
+#[derive(Default)]
+pub struct LdtkLoader;
+
+#[derive(Debug, Error)]
+pub enum LdtkLoaderError
+{
+    #[error("could not read LDtk project: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("could not parse LDtk project: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("invalid LDtk project: {0}")]
+    Invalid(String),
+
+    #[error(transparent)]
+    Questions(#[from] LevelLoaderError)
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LdtkJson
+{
+    #[serde(default)]
+    external_levels: bool,
+    defs: LdtkDefinitions,
+    levels: Vec<LdtkLevel>
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct LdtkDefinitions
+{
+    #[serde(default)]
+    tilesets: Vec<LdtkTileset>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LdtkTileset
+{
+    uid: i64,
+    #[serde(default)]
+    rel_path: Option<String>,
+    tile_grid_size: u32,
+    #[serde(default)]
+    spacing: u32,
+    #[serde(default)]
+    padding: u32,
+    #[serde(rename = "__cWid")]
+    columns: u32,
+    #[serde(rename = "__cHei")]
+    rows: u32
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LdtkLevel
+{
+    identifier: String,
+    px_wid: f32,
+    px_hei: f32,
+    #[serde(default)]
+    bg_rel_path: Option<String>,
+    #[serde(default)]
+    layer_instances: Option<Vec<LdtkLayer>>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LdtkLayer
+{
+    #[serde(rename = "__gridSize")]
+    grid_size: f32,
+    #[serde(rename = "__tilesetDefUid", default)]
+    tileset_uid: Option<i64>,
+    #[serde(default)]
+    grid_tiles: Vec<LdtkTile>,
+    #[serde(default)]
+    auto_layer_tiles: Vec<LdtkTile>,
+    #[serde(default)]
+    entity_instances: Vec<LdtkEntity>
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct LdtkTile
+{
+    px: [f32; 2],
+    t: usize,
+    #[serde(default)]
+    f: u8
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LdtkEntity
+{
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    iid: String,
+    px: [f32; 2],
+    #[serde(rename = "__pivot")]
+    pivot: [f32; 2],
+    width: f32,
+    height: f32,
+    #[serde(default)]
+    field_instances: Vec<LdtkField>
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct LdtkField
+{
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__value")]
+    value: serde_json::Value
+}
+
+impl LdtkEntity
+{
+    fn field(&self, name: &str) -> Option<&serde_json::Value>
+    {
+        self.field_instances
+            .iter()
+            .find(|field| field.identifier == name)
+            .map(|field| &field.value)
+            .filter(|value| !value.is_null())
+    }
+
+    fn string_field(&self, name: &str) -> Option<String>
+    {
+        self.field(name).map(|value| match value
+        {
+            serde_json::Value::String(value) => value.clone(),
+            // Entity references are objects, the referenced entity is identified by its iid
+            serde_json::Value::Object(reference) => reference
+                .get("entityIid")
+                .and_then(|iid| iid.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            value => value.to_string()
+        })
+    }
+
+    fn top_left(&self) -> Vec2
+    {
+        Vec2::new(self.px[0] - self.pivot[0] * self.width, self.px[1] - self.pivot[1] * self.height)
+    }
+}
+
+impl AssetLoader for LdtkLoader
+{
+    type Asset = LdtkProject;
+    type Settings = ();
+    type Error = LdtkLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>>
+    {
+        Box::pin(async move
+        {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+
+            for (identifier, mut level) in parse_project(&bytes, load_context.path())?
+            {
+                load_questions(&mut level, load_context).await?;
+                level.load_dependencies(&format!("{identifier}/"), load_context);
+                load_context.add_labeled_asset(identifier, level);
+            }
+
+            Ok(LdtkProject)
+        })
+    }
+
+    fn extensions(&self) -> &[&str]
+    {
+        &["ldtk"]
+    }
+}
+
+/// Reads every level of a project into a room named by the level identifier,
+/// `path` is the project's location inside the asset folder.
+pub fn parse_project(bytes: &[u8], path: &Path) -> Result<Vec<(String, LevelData)>, LdtkLoaderError>
+{
+    let project = serde_json::from_slice::<LdtkJson>(bytes)?;
+    if project.external_levels
+    {
+        return Err(LdtkLoaderError::Invalid("external level files are not supported".into()));
+    }
+
+    let tilesets: HashMap<i64, TilesetData> = project.defs.tilesets
+        .iter()
+        .filter_map(|tileset| tileset.rel_path.as_ref().map(|image| (tileset.uid, TilesetData
+        {
+            image: resolve_path(path, image),
+            tile_size: Vec2::splat(tileset.tile_grid_size as f32),
+            columns: tileset.columns as usize,
+            rows: tileset.rows as usize,
+            spacing: tileset.spacing as f32,
+            margin: tileset.padding as f32
+        })))
+        .collect();
+
+    project.levels
+        .iter()
+        .map(|level| Ok((level.identifier.clone(), build_level(level, &tilesets, path)?)))
+        .collect()
+}
+
+fn build_level(
+    level: &LdtkLevel,
+    tilesets: &HashMap<i64, TilesetData>,
+    path: &Path
+) -> Result<LevelData, LdtkLoaderError>
+{
+    let to_world = |x: f32, y: f32| Vec2::new(x - level.px_wid / 2., level.px_hei / 2. - y);
+    let to_rect = |entity: &LdtkEntity|
+    {
+        let top_left = entity.top_left();
+        let center = to_world(top_left.x + entity.width / 2., top_left.y + entity.height / 2.);
+        RectData { x: center.x, y: center.y, width: entity.width, height: entity.height }
+    };
+
+    let mut data = LevelData { bounds: Some(RectData::centered(Vec2::ZERO, Vec2::new(level.px_wid, level.px_hei))), ..Default::default() };
+    if let Some(background) = &level.bg_rel_path
+    {
+        data.background = resolve_path(path, background);
+    }
+
+    let Some(layers) = &level.layer_instances else
+    {
+        return Err(LdtkLoaderError::Invalid(format!("level `{}` has no layers", level.identifier)));
+    };
+
+    let mut gate_ids = HashMap::new();
+    let mut quizzes = Vec::new();
+    let mut triggers = Vec::new();
+
+    // LDtk lists the top-most layer first
+    for (depth, layer) in layers.iter().rev().enumerate()
+    {
+        if let Some(tileset) = layer.tileset_uid.and_then(|uid| tilesets.get(&uid))
+        {
+            let tiles: Vec<TileData> = layer.grid_tiles
+                .iter()
+                .chain(layer.auto_layer_tiles.iter())
+                .map(|tile|
+                {
+                    let position = to_world(tile.px[0] + layer.grid_size / 2., tile.px[1] + layer.grid_size / 2.);
+                    TileData
+                    {
+                        index: tile.t,
+                        x: position.x,
+                        y: position.y,
+                        flip_x: tile.f & 1 != 0,
+                        flip_y: tile.f & 2 != 0
+                    }
+                })
+                .collect();
+
+            if !tiles.is_empty()
+            {
+                data.tile_layers.push(TileLayerData { tileset: tileset.clone(), z: -1. + depth as f32 * 0.01, tiles, ..Default::default() });
+            }
+        }
+
+        for entity in layer.entity_instances.iter()
+        {
+            match entity.identifier.to_lowercase().as_str()
+            {
+                "ground" => data.ground.push(to_rect(entity)),
+                "wall" => data.walls.push(to_rect(entity)),
+                "hazard" => match entity.string_field("signal").filter(|signal| !signal.is_empty())
+                {
+                    Some(signal) => data.switched_hazards.push(SwitchedHazardData
+                    {
+                        rect: to_rect(entity),
+                        signal,
+                        inverted: entity.field("inverted").and_then(|inverted| inverted.as_bool()).unwrap_or(false),
+                        texture: entity.string_field("texture").map(|texture| resolve_path(path, &texture)).unwrap_or_default(),
+                        ..Default::default()
+                    }),
+                    None => data.hazards.push(to_rect(entity))
+                },
+                "box" | "crate" => data.boxes.push(to_rect(entity)),
+                "goal" => data.goal = to_rect(entity),
+                "checkpoint" | "starpost" | "star_post" =>
+                {
+                    let rect = to_rect(entity);
+                    data.checkpoints.push(PointData { x: rect.x, y: rect.y });
+                },
+                "ring" =>
+                {
+                    let rect = to_rect(entity);
+                    data.rings.push(PointData { x: rect.x, y: rect.y });
+                },
+                "playerstart" | "player_start" =>
+                {
+                    let position = to_world(entity.px[0], entity.px[1]);
+                    data.player_start = PointData { x: position.x, y: position.y };
+                },
+                "gate" =>
+                {
+                    let id = entity.string_field("id").unwrap_or_else(|| entity.iid.clone());
+                    gate_ids.insert(entity.iid.clone(), id.clone());
+                    gate_ids.insert(id.clone(), id.clone());
+
+                    let lock = match entity.string_field("lock")
+                    {
+                        Some(lock) => LockData::parse(&lock)
+                            .ok_or_else(|| LdtkLoaderError::Invalid(format!("gate `{id}` has an unknown lock `{lock}`")))?,
+                        None => LockData::Quiz
+                    };
+
+                    data.gates.push(GateData
+                    {
+                        id,
+                        texture: entity.string_field("texture").map(|texture| resolve_path(path, &texture)).unwrap_or_default(),
+                        rect: to_rect(entity),
+                        lock,
+                        ..Default::default()
+                    });
+                },
+                "platform" =>
+                {
+                    // Point fields are grid cells
+                    let path_stops = entity.field("path")
+                        .and_then(|stops| stops.as_array())
+                        .map(|stops| stops
+                            .iter()
+                            .filter_map(|stop| Some((stop.get("cx")?.as_f64()? as f32, stop.get("cy")?.as_f64()? as f32)))
+                            .map(|(cx, cy)| to_world((cx + 0.5) * layer.grid_size, (cy + 0.5) * layer.grid_size))
+                            .map(|stop| PointData { x: stop.x, y: stop.y })
+                            .collect())
+                        .unwrap_or_default();
+
+                    data.platforms.push(PlatformData
+                    {
+                        rect: to_rect(entity),
+                        path: path_stops,
+                        speed: entity.field("speed").and_then(|speed| speed.as_f64()).map_or(DEFAULT_PLATFORM_SPEED, |speed| speed as f32),
+                        mode: entity.string_field("mode").and_then(|mode| PlatformPath::parse(&mode)).unwrap_or_default(),
+                        triggered: entity.field("triggered").and_then(|triggered| triggered.as_bool()).unwrap_or(false),
+                        signal: entity.string_field("signal").unwrap_or_default(),
+                        texture: entity.string_field("texture").map(|texture| resolve_path(path, &texture)).unwrap_or_default(),
+                        ..Default::default()
+                    });
+                },
+                "enemy" =>
+                {
+                    let number = |name: &str| entity.field(name).and_then(|value| value.as_f64()).map(|value| value as f32);
+                    data.enemies.push(EnemyData
+                    {
+                        rect: to_rect(entity),
+                        speed: number("speed").unwrap_or(DEFAULT_ENEMY_SPEED),
+                        patrol: number("patrol").unwrap_or_default(),
+                        texture: entity.string_field("texture").map(|texture| resolve_path(path, &texture)).unwrap_or_default(),
+                        frame_width: number("frameWidth").unwrap_or(entity.width),
+                        frame_height: number("frameHeight").unwrap_or(entity.height),
+                        frames: number("frames").map_or(1, |frames| frames as usize),
+                        ..Default::default()
+                    });
+                },
+                "key" =>
+                {
+                    let rect = to_rect(entity);
+                    data.keys.push(KeyData
+                    {
+                        id: entity.string_field("id").unwrap_or_else(|| entity.iid.clone()),
+                        x: rect.x,
+                        y: rect.y,
+                        texture: entity.string_field("texture").map(|texture| resolve_path(path, &texture)).unwrap_or_default(),
+                        ..Default::default()
+                    });
+                },
+                "spring" | "bumper" =>
+                {
+                    let number = |name: &str| entity.field(name).and_then(|value| value.as_f64()).map(|value| value as f32);
+                    let impulse = match (number("impulseX"), number("impulseY"))
+                    {
+                        (None, None) => DEFAULT_SPRING_IMPULSE,
+                        (x, y) => Vec2::new(x.unwrap_or_default(), y.unwrap_or_default())
+                    };
+
+                    data.springs.push(SpringData
+                    {
+                        rect: to_rect(entity),
+                        impulse: PointData { x: impulse.x, y: impulse.y },
+                        kind: SpringKind::parse(&entity.identifier).unwrap_or_default(),
+                        texture: entity.string_field("texture").map(|texture| resolve_path(path, &texture)).unwrap_or_default(),
+                        ..Default::default()
+                    });
+                },
+                "lever" | "switch" | "plate" | "pressureplate" | "pressure_plate" =>
+                {
+                    data.switches.push(SwitchData
+                    {
+                        rect: to_rect(entity),
+                        kind: SwitchKind::parse(&entity.identifier).unwrap_or_default(),
+                        signal: entity.string_field("signal").unwrap_or_default(),
+                        on: entity.field("on").and_then(|on| on.as_bool()).unwrap_or(false),
+                        texture: entity.string_field("texture").map(|texture| resolve_path(path, &texture)).unwrap_or_default(),
+                        ..Default::default()
+                    });
+                },
+                "quiz" => quizzes.push(entity),
+                "trigger" => triggers.push(entity),
+                _ => {}
+            }
+        }
+    }
+
+    let mut quiz_ids = HashMap::new();
+    for entity in quizzes
+    {
+        let id = entity.string_field("id").unwrap_or_else(|| entity.iid.clone());
+        quiz_ids.insert(entity.iid.clone(), id.clone());
+        quiz_ids.insert(id.clone(), id.clone());
+
+        let answers: Vec<String> = entity.field("answers")
+            .and_then(|answers| answers.as_array())
+            .map(|answers| answers.iter().map(|answer| answer.as_str().unwrap_or_default().to_string()).collect())
+            .unwrap_or_default();
+
+        // A single answer or an array of them
+        let correct: Vec<usize> = match entity.field("correct")
+        {
+            Some(serde_json::Value::Array(correct)) => correct.iter().filter_map(|correct| correct.as_u64()).map(|correct| correct as usize).collect(),
+            Some(correct) => correct.as_u64().into_iter().map(|correct| correct as usize).collect(),
+            None => vec![1]
+        };
+
+        let kind = match entity.string_field("kind")
+        {
+            Some(kind) => QuizKind::parse(&kind).ok_or_else(|| LdtkLoaderError::Invalid(format!("quiz {} has unknown kind `{kind}`", entity.iid)))?,
+            None => QuizKind::default()
+        };
+        let gate = entity.string_field("gate").and_then(|gate| gate_ids.get(&gate).cloned());
+        let actions = match entity.string_field("actions")
+        {
+            Some(actions) => QuizAction::parse_list(&actions)
+                .ok_or_else(|| LdtkLoaderError::Invalid(format!("quiz {} has unknown actions `{actions}`", entity.iid)))?,
+            None => Vec::new()
+        };
+
+        let mut quiz = QuizData::from_answers(
+            to_rect(entity),
+            gate,
+            kind,
+            entity.string_field("question").unwrap_or_default(),
+            &answers,
+            &correct
+        );
+        quiz.id = id;
+
+        if let Some(normalize) = entity.string_field("normalize")
+        {
+            quiz.normalize = Normalization::parse(&normalize)
+                .ok_or_else(|| LdtkLoaderError::Invalid(format!("quiz {} has unknown normalization `{normalize}`", entity.iid)))?;
+        }
+
+        if let Some(numeric) = quiz.numeric.as_mut()
+        {
+            if let Some(tolerance) = entity.string_field("tolerance")
+            {
+                numeric.parse_tolerance(&tolerance)
+                    .ok_or_else(|| LdtkLoaderError::Invalid(format!("quiz {} has unknown tolerance `{tolerance}`", entity.iid)))?;
+            }
+
+            if let Some(units) = entity.string_field("units")
+            {
+                numeric.parse_units(&units)
+                    .ok_or_else(|| LdtkLoaderError::Invalid(format!("quiz {} has unknown units `{units}`", entity.iid)))?;
+            }
+        }
+        else if kind == QuizKind::Numeric && entity.string_field("bank").is_none()
+        {
+            return Err(LdtkLoaderError::Invalid(format!("quiz {} has no number for its answer", entity.iid)));
+        }
+
+        if let Some(bank) = entity.string_field("bank")
+        {
+            let pick = entity.string_field("pick").unwrap_or_default();
+            quiz.from_bank = Some(QuestionRef::parse(resolve_path(path, &bank), &pick)
+                .ok_or_else(|| LdtkLoaderError::Invalid(format!("quiz {} has unknown pick `{pick}`", entity.iid)))?);
+        }
+
+        // Reward spots are in level pixels
+        quiz.actions = actions
+            .into_iter()
+            .map(|action| match action
+            {
+                QuizAction::Unlock(id) => QuizAction::Unlock(gate_ids.get(&id).cloned().unwrap_or(id)),
+                QuizAction::Open(id) => QuizAction::Open(gate_ids.get(&id).cloned().unwrap_or(id)),
+                QuizAction::Reward { x, y, rings } =>
+                {
+                    let spot = to_world(x, y);
+                    QuizAction::Reward { x: spot.x, y: spot.y, rings }
+                },
+                action => action
+            })
+            .collect();
+
+        data.quizzes.push(quiz);
+    }
+
+    for entity in triggers
+    {
+        let when = match (entity.string_field("when"), entity.string_field("quiz"))
+        {
+            (Some(when), Some(quiz)) if when == "correct" || when == "wrong" => format!("{when}:{quiz}"),
+            (when, _) => when.unwrap_or_else(|| String::from("enter"))
+        };
+
+        let when = TriggerEvent::parse(&when, to_rect(entity), |quiz| quiz_ids.get(quiz).cloned())
+            .ok_or_else(|| LdtkLoaderError::Invalid(format!("trigger {} has unknown event `{when}`", entity.iid)))?;
+
+        let then = entity.string_field("then").unwrap_or_default();
+        let then = TriggerAction::parse_list(&then)
+            .ok_or_else(|| LdtkLoaderError::Invalid(format!("trigger {} has unknown actions `{then}`", entity.iid)))?;
+
+        data.triggers.push(TriggerData
+        {
+            when,
+            then: then
+                .into_iter()
+                .map(|action| match action
+                {
+                    TriggerAction::OpenGate(id) => TriggerAction::OpenGate(gate_ids.get(&id).cloned().unwrap_or(id)),
+                    TriggerAction::Reward { x, y, rings } =>
+                    {
+                        let spot = to_world(x, y);
+                        TriggerAction::Reward { x: spot.x, y: spot.y, rings }
+                    },
+                    TriggerAction::Sound(sound) => TriggerAction::Sound(resolve_path(path, &sound)),
+                    action => action
+                })
+                .collect(),
+            repeat: entity.field("repeat").and_then(|repeat| repeat.as_bool()).unwrap_or(false)
+        });
+    }
+
+    Ok(data)
+}
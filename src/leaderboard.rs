@@ -0,0 +1,346 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use bevy::asset::io::file::FileAssetReader;
+use bevy::asset::AssetServer;
+use bevy::ecs::change_detection::DetectChanges;
+use bevy::ecs::component::Component;
+use bevy::ecs::query::With;
+use bevy::ecs::system::Commands;
+use bevy::ecs::system::Query;
+use bevy::ecs::system::Res;
+use bevy::ecs::system::ResMut;
+use bevy::ecs::system::Resource;
+use bevy::hierarchy::BuildChildren;
+use bevy::input::keyboard::KeyCode;
+use bevy::input::Input;
+use bevy::log::warn;
+use bevy::render::color::Color;
+use bevy::text::Text;
+use bevy::text::TextStyle;
+use bevy::time::Time;
+use bevy::time::Timer;
+use bevy::time::TimerMode;
+use bevy::ui::node_bundles::NodeBundle;
+use bevy::ui::node_bundles::TextBundle;
+use bevy::ui::AlignItems;
+use bevy::ui::JustifyContent;
+use bevy::ui::PositionType;
+use bevy::ui::Style;
+use bevy::ui::Val;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::assets::GameAssets;
+use crate::level::CurrentLevelData;
+use crate::lives::Lives;
+use crate::rings::Score;
+use crate::rings::RING_BONUS;
+use crate::shuffle::QuizRng;
+use crate::system::CurrentLevel;
+use crate::system::GameState;
+
+/// Where the leaderboard is kept, next to the `assets` folder.
+const LEADERBOARD_FILE: &str = "leaderboard.ron";
+
+/// Runs kept for every room.
+const ENTRIES_PER_LEVEL: usize = 5;
+
+/// How long the results are shown before the next room, in seconds.
+const RESULTS_DURATION: f32 = 8.;
+
+/// The run through the current room. After a death the clock goes back to the checkpoint, the answers stay counted.
+#[derive(Resource, Debug, Default)]
+pub struct LevelStats
+{
+    /// Names the room in the leaderboard, usually its asset path.
+    pub level: String,
+    pub time: f32,
+    pub answers: u32,
+    pub correct_answers: u32
+}
+
+impl LevelStats
+{
+    pub fn answer(&mut self, is_correct: bool)
+    {
+        self.answers += 1;
+        self.correct_answers += is_correct as u32;
+    }
+
+    /// Share of correct answers in percent, rooms without answers count as perfect.
+    pub fn accuracy(&self) -> u32
+    {
+        accuracy(self.answers, self.correct_answers)
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct LeaderboardEntry
+{
+    pub time: f32,
+    pub answers: u32,
+    pub correct_answers: u32,
+    pub rings: u32,
+    /// Replays the answer order of the run with `--seed`, see `QuizRng`.
+    #[serde(default)]
+    pub seed: u64
+}
+
+impl LeaderboardEntry
+{
+    pub fn accuracy(&self) -> u32
+    {
+        accuracy(self.answers, self.correct_answers)
+    }
+}
+
+/// The fastest runs of every room, saved to `leaderboard.ron`.
+#[derive(Resource, Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Leaderboard
+{
+    pub levels: BTreeMap<String, Vec<LeaderboardEntry>>
+}
+
+impl Leaderboard
+{
+    fn path() -> PathBuf
+    {
+        FileAssetReader::get_base_path().join(LEADERBOARD_FILE)
+    }
+
+    /// A missing or broken file gives an empty leaderboard.
+    pub fn load() -> Self
+    {
+        let Ok(text) = std::fs::read_to_string(Self::path()) else
+        {
+            return Leaderboard::default();
+        };
+
+        ron::de::from_str(&text).unwrap_or_else(|error|
+        {
+            warn!("Ignoring {LEADERBOARD_FILE}: {error}");
+            Leaderboard::default()
+        })
+    }
+
+    pub fn save(&self) -> Result<(), String>
+    {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::new().indentor(String::from("    ")))
+            .map_err(|error| error.to_string())?;
+
+        std::fs::write(Self::path(), text).map_err(|error| format!("{}: {error}", Self::path().display()))
+    }
+
+    pub fn best(&self, level: &str) -> Option<&LeaderboardEntry>
+    {
+        self.levels.get(level).and_then(|entries| entries.first())
+    }
+
+    /// Keeps the entry if it is among the fastest, returns its place starting from 0.
+    pub fn add(&mut self, level: &str, entry: LeaderboardEntry) -> Option<usize>
+    {
+        let entries = self.levels.entry(level.to_string()).or_default();
+        let place = entries.iter().position(|other| entry.time < other.time).unwrap_or(entries.len());
+        entries.insert(place, entry);
+        entries.truncate(ENTRIES_PER_LEVEL);
+
+        (place < ENTRIES_PER_LEVEL).then_some(place)
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct TimerLabel;
+
+/// Counts down the results screen shown between rooms.
+#[derive(Resource, Debug)]
+pub struct Results(pub Timer);
+
+fn accuracy(answers: u32, correct_answers: u32) -> u32
+{
+    (correct_answers * 100).checked_div(answers).unwrap_or(100)
+}
+
+/// Minutes, seconds and hundredths, like `1:05.42`.
+pub fn format_time(time: f32) -> String
+{
+    let hundredths = (time * 100.).round() as u32;
+    format!("{}:{:02}.{:02}", hundredths / 6000, hundredths / 100 % 60, hundredths % 100)
+}
+
+pub fn load_leaderboard(mut commands: Commands)
+{
+    commands.insert_resource(Leaderboard::load());
+}
+
+/// Starts the clock for a new room. A room rebuilt after a death takes the time it had at the checkpoint,
+/// or starts from zero again without one.
+pub fn start_level_timer(
+    mut commands: Commands,
+    current_level: CurrentLevelData,
+    game_stats: Res<CurrentLevel>,
+    asset_server: Res<AssetServer>,
+    lives: Res<Lives>,
+    mut stats: ResMut<LevelStats>
+) {
+    if lives.respawned
+    {
+        stats.time = current_level.checkpoint().map_or(0., |checkpoint| checkpoint.time);
+        return;
+    }
+
+    let level = current_level.handle()
+        .and_then(|handle| asset_server.get_path(handle.id()))
+        .map(|path| path.to_string())
+        .unwrap_or_else(|| format!("room {}", game_stats.0));
+
+    commands.insert_resource(LevelStats { level, ..Default::default() });
+}
+
+pub fn tick_level_timer(time: Res<Time>, mut stats: ResMut<LevelStats>)
+{
+    stats.time += time.delta_seconds();
+}
+
+pub fn spawn_timer_label(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>
+) {
+    commands.spawn((
+        TextBundle::from_section(
+            format!("Время: {}", format_time(0.)),
+            TextStyle {
+                font: game_assets.main_font.clone(),
+                font_size: 30.0,
+                color: Color::WHITE
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(75.0),
+            left: Val::Px(10.0),
+            ..Default::default()
+        }),
+        TimerLabel,
+        GameState::InGame
+    ));
+}
+
+pub fn update_timer_label(
+    stats: Res<LevelStats>,
+    mut labels: Query<&mut Text, With<TimerLabel>>
+) {
+    if !stats.is_changed()
+    {
+        return;
+    }
+
+    for mut text in labels.iter_mut()
+    {
+        text.sections[0].value = format!("Время: {}", format_time(stats.time));
+    }
+}
+
+/// Adds the finished run to the leaderboard and shows how it went.
+pub fn show_results(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    stats: Res<LevelStats>,
+    score: Res<Score>,
+    rng: Res<QuizRng>,
+    mut leaderboard: ResMut<Leaderboard>
+) {
+    let entry = LeaderboardEntry
+    {
+        time: stats.time,
+        answers: stats.answers,
+        correct_answers: stats.correct_answers,
+        rings: score.rings,
+        seed: rng.seed
+    };
+
+    let previous_best = leaderboard.best(&stats.level).map(|best| best.time);
+    let place = leaderboard.add(&stats.level, entry);
+    if let Err(error) = leaderboard.save()
+    {
+        warn!("Could not save the leaderboard: {error}");
+    }
+
+    let record = match previous_best
+    {
+        Some(best) if stats.time < best => String::from(" - новый рекорд!"),
+        Some(best) => format!(" (рекорд: {})", format_time(best)),
+        None => String::new()
+    };
+
+    let mut lines = vec![
+        String::from("Комната пройдена!"),
+        format!("Время: {}{record}", format_time(stats.time)),
+        format!("Ответы: {} из {} ({}%)", stats.correct_answers, stats.answers, stats.accuracy()),
+        format!("Кольца: {} x {RING_BONUS} = {}", score.rings, score.rings * RING_BONUS),
+        format!("Счёт: {}", score.total),
+        format!("Сид: {}", rng.seed),
+        String::new(),
+        String::from("Лучшие результаты:")
+    ];
+
+    for (index, entry) in leaderboard.levels.get(&stats.level).into_iter().flatten().enumerate()
+    {
+        let marker = if place == Some(index) { " <" } else { "" };
+        lines.push(format!("{}. {} - {}%{marker}", index + 1, format_time(entry.time), entry.accuracy()));
+    }
+
+    commands.spawn((
+        NodeBundle
+        {
+            style: Style
+            {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        GameState::LevelCompleted
+    ))
+    .with_children(|parent|
+    {
+        parent.spawn((
+            TextBundle::from_section(lines.join("\n"), TextStyle
+            {
+                font: game_assets.main_font.clone(),
+                font_size: 40.0,
+                color: Color::WHITE
+            }),
+            GameState::LevelCompleted
+        ));
+    });
+
+    commands.insert_resource(Results(Timer::from_seconds(RESULTS_DURATION, TimerMode::Once)));
+}
+
+pub fn clear_results(mut commands: Commands)
+{
+    commands.remove_resource::<Results>();
+}
+
+/// Enter or Space skip the rest of the results.
+pub fn update_results(
+    time: Res<Time>,
+    keys: Res<Input<KeyCode>>,
+    mut results: ResMut<Results>
+) {
+    results.0.tick(time.delta());
+    if keys.any_just_pressed([KeyCode::Return, KeyCode::Space])
+    {
+        let duration = results.0.duration();
+        results.0.set_elapsed(duration);
+    }
+}
+
+pub fn results_finished(results: Option<Res<Results>>) -> bool
+{
+    results.is_some_and(|results| results.0.finished())
+}
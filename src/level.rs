@@ -0,0 +1,538 @@
+use std::path::Component as PathComponent;
+use std::path::Path;
+use std::path::PathBuf;
+
+use bevy::asset::io::Reader;
+use bevy::asset::Asset;
+use bevy::asset::AssetLoader;
+use bevy::asset::Assets;
+use bevy::asset::AsyncReadExt;
+use bevy::asset::Handle;
+use bevy::asset::LoadContext;
+use bevy::asset::ReadAssetBytesError;
+use bevy::ecs::entity::Entity;
+use bevy::ecs::system::Commands;
+use bevy::ecs::system::Res;
+use bevy::ecs::system::SystemParam;
+use bevy::math::Vec2;
+use bevy::reflect::TypePath;
+use bevy::render::texture::Image;
+use bevy::render::view::Visibility;
+use bevy::sprite::SpriteBundle;
+use bevy::sprite::SpriteSheetBundle;
+use bevy::sprite::TextureAtlas;
+use bevy::sprite::TextureAtlasSprite;
+use bevy::transform::components::Transform;
+use bevy::utils::BoxedFuture;
+use bevy_xpbd_2d::components::Collider;
+use bevy_xpbd_2d::components::CollisionLayers;
+use bevy_xpbd_2d::components::RigidBody;
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::assets::GameAssets;
+use crate::campaign::Campaign;
+use crate::checkpoint::spawn_star_posts;
+use crate::checkpoint::Checkpoint;
+use crate::enemy::spawn_enemy;
+use crate::enemy::EnemyData;
+use crate::interactable::spawn_quizzes;
+use crate::interactable::GroundObject;
+use crate::interactable::QuizData;
+use crate::lives::LifeBonus;
+use crate::locks::spawn_gates;
+use crate::locks::spawn_keys;
+use crate::locks::GateData;
+use crate::locks::KeyData;
+use crate::platform::spawn_platforms;
+use crate::platform::PlatformData;
+use crate::player::Layer;
+use crate::questions::load_questions;
+use crate::rings::spawn_ring;
+use crate::spring::spawn_springs;
+use crate::spring::SpringData;
+use crate::switches::spawn_boxes;
+use crate::switches::spawn_switched_hazards;
+use crate::switches::spawn_switches;
+use crate::switches::SwitchData;
+use crate::switches::SwitchedHazardData;
+use crate::system::CurrentLevel;
+use crate::system::GameState;
+use crate::triggers::TriggerAction;
+use crate::triggers::TriggerData;
+
+/// Size of the area the camera shows, in world units.
+pub const VIEWPORT_SIZE: Vec2 = Vec2::new(320., 224.);
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct PointData
+{
+    pub x: f32,
+    pub y: f32
+}
+
+/// Axis-aligned box given by its center and full size, in world units.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct RectData
+{
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32
+}
+
+impl RectData
+{
+    pub fn centered(center: Vec2, size: Vec2) -> Self
+    {
+        RectData { x: center.x, y: center.y, width: size.x, height: size.y }
+    }
+
+    pub fn min(&self) -> Vec2
+    {
+        Vec2::new(self.x - self.width / 2., self.y - self.height / 2.)
+    }
+
+    pub fn max(&self) -> Vec2
+    {
+        Vec2::new(self.x + self.width / 2., self.y + self.height / 2.)
+    }
+
+    pub fn overlaps(&self, other: &RectData) -> bool
+    {
+        let (min, max) = (self.min(), self.max());
+        let (other_min, other_max) = (other.min(), other.max());
+        min.x < other_max.x && other_min.x < max.x && min.y < other_max.y && other_min.y < max.y
+    }
+}
+
+/// Grid of equally sized tiles cut from one image.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TilesetData
+{
+    pub image: String,
+    pub tile_size: Vec2,
+    pub columns: usize,
+    pub rows: usize,
+    pub spacing: f32,
+    pub margin: f32
+}
+
+/// Tiles of one map layer, all cut from the same tileset.
+#[derive(Clone, Debug, Default)]
+pub struct TileLayerData
+{
+    pub tileset: TilesetData,
+    pub z: f32,
+    pub tiles: Vec<TileData>,
+    pub atlas: Handle<TextureAtlas>
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TileData
+{
+    pub index: usize,
+    pub x: f32,
+    pub y: f32,
+    pub flip_x: bool,
+    pub flip_y: bool
+}
+
+/// A single room, loaded from a `*.level.ron` file or imported from a map editor.
+#[derive(Asset, TypePath, Clone, Debug, Default, Deserialize, Serialize)]
+pub struct LevelData
+{
+    #[serde(default)]
+    pub background: String,
+    pub player_start: PointData,
+    #[serde(default)]
+    pub ground: Vec<RectData>,
+    #[serde(default)]
+    pub walls: Vec<RectData>,
+    #[serde(default)]
+    pub gates: Vec<GateData>,
+    #[serde(default)]
+    pub hazards: Vec<RectData>,
+    #[serde(default)]
+    pub quizzes: Vec<QuizData>,
+    #[serde(default)]
+    pub checkpoints: Vec<PointData>,
+    #[serde(default)]
+    pub rings: Vec<PointData>,
+    #[serde(default)]
+    pub platforms: Vec<PlatformData>,
+    #[serde(default)]
+    pub enemies: Vec<EnemyData>,
+    #[serde(default)]
+    pub springs: Vec<SpringData>,
+    #[serde(default)]
+    pub keys: Vec<KeyData>,
+    #[serde(default)]
+    pub switches: Vec<SwitchData>,
+    #[serde(default)]
+    pub switched_hazards: Vec<SwitchedHazardData>,
+    /// Pushable boxes, they can hold pressure plates down.
+    #[serde(default)]
+    pub boxes: Vec<RectData>,
+    #[serde(default)]
+    pub triggers: Vec<TriggerData>,
+    pub goal: RectData,
+    /// Extra lives for reaching the goal.
+    #[serde(default)]
+    pub life_bonus: u8,
+    /// Area the camera may show, rooms without it fit on a single screen.
+    #[serde(default)]
+    pub bounds: Option<RectData>,
+
+    #[serde(skip)]
+    pub background_image: Option<Handle<Image>>,
+
+    #[serde(skip)]
+    pub tile_layers: Vec<TileLayerData>
+}
+
+impl LevelData
+{
+    pub fn parse(bytes: &[u8]) -> Result<Self, LevelLoaderError>
+    {
+        Ok(ron::de::from_bytes::<LevelData>(bytes)?)
+    }
+
+    pub fn camera_bounds(&self) -> RectData
+    {
+        self.bounds.unwrap_or(RectData::centered(Vec2::ZERO, VIEWPORT_SIZE))
+    }
+
+    /// Writes the room in the `*.level.ron` format. Tiles imported from map editors are not kept,
+    /// quizzes that draw from a bank keep the reference instead of the question it was filled with.
+    pub fn to_ron(&self) -> Result<String, ron::Error>
+    {
+        let mut level = self.clone();
+        for quiz in level.quizzes.iter_mut().filter(|quiz| quiz.from_bank.is_some())
+        {
+            *quiz = QuizData
+            {
+                rect: quiz.rect,
+                gate: quiz.gate.take(),
+                actions: std::mem::take(&mut quiz.actions),
+                from_bank: quiz.from_bank.take(),
+                ..Default::default()
+            };
+        }
+
+        ron::ser::to_string_pretty(&level, ron::ser::PrettyConfig::new().indentor(String::from("    ")))
+    }
+
+    /// Starts loading the images the room refers to. Tileset atlases are added as labeled
+    /// assets, `label_prefix` keeps them apart when one file holds several rooms.
+    pub fn load_dependencies(&mut self, label_prefix: &str, load_context: &mut LoadContext)
+    {
+        if !self.background.is_empty()
+        {
+            self.background_image = Some(load_context.load(self.background.clone()));
+        }
+
+        for gate in self.gates.iter_mut().filter(|gate| !gate.texture.is_empty())
+        {
+            gate.image = load_context.load(gate.texture.clone());
+        }
+
+        for platform in self.platforms.iter_mut().filter(|platform| !platform.texture.is_empty())
+        {
+            platform.image = load_context.load(platform.texture.clone());
+        }
+
+        for key in self.keys.iter_mut().filter(|key| !key.texture.is_empty())
+        {
+            key.image = load_context.load(key.texture.clone());
+        }
+
+        for switch in self.switches.iter_mut().filter(|switch| !switch.texture.is_empty())
+        {
+            switch.image = load_context.load(switch.texture.clone());
+        }
+
+        for hazard in self.switched_hazards.iter_mut().filter(|hazard| !hazard.texture.is_empty())
+        {
+            hazard.image = load_context.load(hazard.texture.clone());
+        }
+
+        for spring in self.springs.iter_mut().filter(|spring| !spring.texture.is_empty())
+        {
+            spring.image = load_context.load(spring.texture.clone());
+        }
+
+        for (index, enemy) in self.enemies.iter_mut().enumerate().filter(|(_, enemy)| !enemy.texture.is_empty())
+        {
+            enemy.load_atlas(format!("{label_prefix}enemy{index}"), load_context);
+        }
+
+        let spawned = self.triggers
+            .iter_mut()
+            .flat_map(|trigger| trigger.then.iter_mut())
+            .filter_map(|action| match action
+            {
+                TriggerAction::SpawnEnemy(enemy) if !enemy.texture.is_empty() => Some(enemy),
+                _ => None
+            });
+
+        for (index, enemy) in spawned.enumerate()
+        {
+            enemy.load_atlas(format!("{label_prefix}trigger_enemy{index}"), load_context);
+        }
+
+        let mut atlases: Vec<(TilesetData, Handle<TextureAtlas>)> = Vec::new();
+        for layer in self.tile_layers.iter_mut()
+        {
+            if let Some((_, atlas)) = atlases.iter().find(|(tileset, _)| *tileset == layer.tileset)
+            {
+                layer.atlas = atlas.clone();
+                continue;
+            }
+
+            let atlas = TextureAtlas::from_grid(
+                load_context.load(layer.tileset.image.clone()),
+                layer.tileset.tile_size,
+                layer.tileset.columns,
+                layer.tileset.rows,
+                Some(Vec2::splat(layer.tileset.spacing)),
+                Some(Vec2::splat(layer.tileset.margin))
+            );
+
+            layer.atlas = load_context.add_labeled_asset(format!("{label_prefix}tileset{}", atlases.len()), atlas);
+            atlases.push((layer.tileset.clone(), layer.atlas.clone()));
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+#[derive(Debug, Error)]
+pub enum LevelLoaderError
+{
+    #[error("could not read level file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("could not parse level file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+
+    #[error("could not read question bank: {0}")]
+    Bank(#[from] ReadAssetBytesError),
+
+    #[error("could not pick a question: {0}")]
+    Question(String)
+}
+
+impl AssetLoader for LevelLoader
+{
+    type Asset = LevelData;
+    type Settings = ();
+    type Error = LevelLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>>
+    {
+        Box::pin(async move
+        {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+
+            let mut level = LevelData::parse(&bytes)?;
+            load_questions(&mut level, load_context).await?;
+            level.load_dependencies("", load_context);
+            Ok(level)
+        })
+    }
+
+    fn extensions(&self) -> &[&str]
+    {
+        &["level.ron"]
+    }
+}
+
+/// Walls and closed gates stop the player, pushable boxes and scattered rings.
+pub fn solid_layers() -> CollisionLayers
+{
+    CollisionLayers::new([Layer::Ground], [Layer::Player, Layer::Ground, Layer::Interactable])
+}
+
+/// Resolves `CurrentLevel` through the campaign to the loaded room.
+#[derive(SystemParam)]
+pub struct CurrentLevelData<'w>
+{
+    game_assets: Res<'w, GameAssets>,
+    campaigns: Res<'w, Assets<Campaign>>,
+    levels: Res<'w, Assets<LevelData>>,
+    current_level: Res<'w, CurrentLevel>,
+    checkpoint: Option<Res<'w, Checkpoint>>
+}
+
+impl<'w> CurrentLevelData<'w>
+{
+    pub fn get(&self) -> Option<&LevelData>
+    {
+        self.handle().and_then(|handle| self.levels.get(handle))
+    }
+
+    /// The checkpoint the player reached in this room, if any.
+    pub fn checkpoint(&self) -> Option<&Checkpoint>
+    {
+        self.checkpoint.as_deref().filter(|checkpoint| checkpoint.level == self.current_level.0)
+    }
+
+    pub fn player_start(&self) -> Vec2
+    {
+        match (self.checkpoint(), self.get())
+        {
+            (Some(checkpoint), _) => checkpoint.position,
+            (None, Some(level)) => Vec2::new(level.player_start.x, level.player_start.y),
+            (None, None) => Vec2::ZERO
+        }
+    }
+
+    pub fn handle(&self) -> Option<&Handle<LevelData>>
+    {
+        self.campaigns
+            .get(&self.game_assets.campaign)
+            .and_then(|campaign| campaign.level(self.current_level.0))
+    }
+}
+
+pub fn spawn_level(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    current_level: CurrentLevelData
+) {
+    let Some(level) = current_level.get() else
+    {
+        return;
+    };
+
+    let checkpoint = current_level.checkpoint();
+    spawn_scenery(&mut commands, level, GameState::InGame);
+
+    for ground in level.ground.iter()
+    {
+        spawn_collider(&mut commands, ground, CollisionLayers::new([Layer::Ground], [Layer::Player, Layer::Enemy, Layer::Ground, Layer::Interactable]));
+    }
+
+    // Uninteractable walls
+    for wall in level.walls.iter()
+    {
+        spawn_collider(&mut commands, wall, solid_layers());
+    }
+
+    spawn_gates(&mut commands, &level.gates, checkpoint);
+
+    // Spikes
+    for hazard in level.hazards.iter()
+    {
+        let entity = spawn_collider(&mut commands, hazard, CollisionLayers::new([Layer::Enemy], [Layer::Player]));
+        commands.entity(entity).insert(GroundObject { next_game_state: GameState::GameOver });
+    }
+
+    spawn_switched_hazards(&mut commands, &level.switched_hazards);
+    spawn_switches(&mut commands, &level.switches, checkpoint);
+    spawn_boxes(&mut commands, &level.boxes);
+    spawn_quizzes(&mut commands, &level.quizzes);
+    spawn_star_posts(&mut commands, &game_assets.star_post, &level.checkpoints, checkpoint);
+    spawn_keys(&mut commands, &level.keys, checkpoint);
+
+    for ring in level.rings.iter()
+    {
+        spawn_ring(&mut commands, Vec2::new(ring.x, ring.y));
+    }
+
+    spawn_platforms(&mut commands, &level.platforms);
+
+    for enemy in level.enemies.iter()
+    {
+        spawn_enemy(&mut commands, enemy);
+    }
+
+    spawn_springs(&mut commands, &level.springs);
+
+    // Level Goal
+    let goal = spawn_collider(&mut commands, &level.goal, CollisionLayers::new([Layer::Enemy], [Layer::Player]));
+    commands.entity(goal).insert((GroundObject { next_game_state: GameState::LevelCompleted }, LifeBonus(level.life_bonus)));
+}
+
+/// Spawns the background and tiles of a room, they only exist while `state` is active.
+pub fn spawn_scenery(commands: &mut Commands, level: &LevelData, state: GameState)
+{
+    if let Some(background) = &level.background_image
+    {
+        commands.spawn((
+            SpriteBundle
+            {
+                texture: background.clone(),
+                transform: Transform::from_xyz(0., 0., -1.),
+                ..Default::default()
+            },
+            state
+        ));
+    }
+
+    for layer in level.tile_layers.iter()
+    {
+        for tile in layer.tiles.iter()
+        {
+            commands.spawn((
+                SpriteSheetBundle
+                {
+                    texture_atlas: layer.atlas.clone(),
+                    sprite: TextureAtlasSprite
+                    {
+                        index: tile.index,
+                        flip_x: tile.flip_x,
+                        flip_y: tile.flip_y,
+                        ..Default::default()
+                    },
+                    transform: Transform::from_xyz(tile.x, tile.y, layer.z),
+                    ..Default::default()
+                },
+                state
+            ));
+        }
+    }
+}
+
+/// An invisible static box, the ground, walls, spikes and quizzes are all made of them.
+pub fn spawn_collider(commands: &mut Commands, rect: &RectData, layers: CollisionLayers) -> Entity
+{
+    commands.spawn((
+        SpriteBundle
+        {
+            visibility: Visibility::Hidden,
+            transform: Transform::from_xyz(rect.x, rect.y, 0.),
+            ..Default::default()
+        },
+        RigidBody::Static,
+        Collider::cuboid(rect.width, rect.height),
+        layers,
+        GameState::InGame
+    )).id()
+}
+
+/// Map editors store paths relative to the map file, Bevy wants them relative to the asset folder.
+pub fn resolve_path(map_path: &Path, relative: &str) -> String
+{
+    let mut resolved = PathBuf::new();
+    for component in map_path.parent().unwrap_or(Path::new("")).join(relative).components()
+    {
+        match component
+        {
+            PathComponent::ParentDir => { resolved.pop(); },
+            PathComponent::CurDir => {},
+            component => resolved.push(component)
+        }
+    }
+
+    resolved.to_string_lossy().replace('\\', "/")
+}
@@ -0,0 +1,27 @@
+pub mod system;
+pub mod animations;
+pub mod player;
+pub mod interactable;
+pub mod assets;
+pub mod level;
+pub mod campaign;
+pub mod tiled;
+pub mod ldtk;
+pub mod validate;
+pub mod reachability;
+pub mod editor;
+pub mod debug;
+pub mod camera;
+pub mod checkpoint;
+pub mod lives;
+pub mod platform;
+pub mod enemy;
+pub mod rings;
+pub mod leaderboard;
+pub mod spring;
+pub mod locks;
+pub mod switches;
+pub mod triggers;
+pub mod questions;
+pub mod answers;
+pub mod shuffle;
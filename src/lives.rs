@@ -0,0 +1,217 @@
+use bevy::ecs::change_detection::DetectChanges;
+use bevy::ecs::component::Component;
+use bevy::ecs::entity::Entity;
+use bevy::ecs::query::Added;
+use bevy::ecs::query::Or;
+use bevy::ecs::query::With;
+use bevy::ecs::schedule::NextState;
+use bevy::ecs::system::Commands;
+use bevy::ecs::system::Query;
+use bevy::ecs::system::Res;
+use bevy::ecs::system::ResMut;
+use bevy::ecs::system::Resource;
+use bevy::render::color::Color;
+use bevy::render::view::Visibility;
+use bevy::text::Text;
+use bevy::text::TextStyle;
+use bevy::time::Time;
+use bevy::time::Timer;
+use bevy::time::TimerMode;
+use bevy::ui::node_bundles::TextBundle;
+use bevy::ui::PositionType;
+use bevy::ui::Style;
+use bevy::ui::Val;
+
+use crate::assets::GameAssets;
+use crate::player::Immobile;
+use crate::player::Player;
+use crate::system::GameState;
+
+pub const STARTING_LIVES: u8 = 3;
+pub const MAX_LIVES: u8 = 9;
+
+/// Correct answers in a row needed for an extra life.
+pub const STREAK_FOR_LIFE: u8 = 3;
+
+/// How long the player lies on the ground before the room is rebuilt, in seconds.
+const DEATH_DURATION: f32 = 1.;
+
+/// How long hazards ignore the player after a respawn, in seconds.
+const INVULNERABILITY_DURATION: f32 = 2.;
+
+const BLINK_INTERVAL: f32 = 0.1;
+
+#[derive(Resource, Debug)]
+pub struct Lives
+{
+    pub count: u8,
+    pub streak: u8,
+    /// Set while the room is rebuilt after a death, so the new player starts out protected.
+    pub respawned: bool
+}
+
+impl Default for Lives
+{
+    fn default() -> Self
+    {
+        Lives { count: STARTING_LIVES, streak: 0, respawned: false }
+    }
+}
+
+impl Lives
+{
+    pub fn award(&mut self, lives: u8)
+    {
+        self.count = self.count.saturating_add(lives).min(MAX_LIVES);
+    }
+
+    /// Counts a quiz answer towards the streak, returns whether it earned a life.
+    pub fn answer(&mut self, is_correct: bool) -> bool
+    {
+        self.streak = if is_correct { self.streak + 1 } else { 0 };
+        if self.streak >= STREAK_FOR_LIFE
+        {
+            self.streak = 0;
+            self.award(1);
+            return true;
+        }
+
+        false
+    }
+}
+
+/// The player has lost a life and waits for the room to be rebuilt.
+#[derive(Component, Debug)]
+pub struct Dying(pub Timer);
+
+/// The player got hit this frame, `take_hits` decides what it costs them.
+#[derive(Component, Debug)]
+pub struct Hurt;
+
+/// Hazards ignore the player until the timer runs out.
+#[derive(Component, Debug)]
+pub struct Invulnerable(pub Timer);
+
+/// Players nothing can hurt right now.
+pub type Protected = Or<(With<Dying>, With<Invulnerable>)>;
+
+/// Lives awarded for reaching the goal it is attached to.
+#[derive(Component, Debug)]
+pub struct LifeBonus(pub u8);
+
+#[derive(Component, Debug)]
+pub struct LivesLabel;
+
+/// Marks `player` as hit by a hazard, an enemy or a wrong answer.
+pub fn hurt_player(commands: &mut Commands, player: Entity)
+{
+    commands.entity(player).insert(Hurt);
+}
+
+/// Starts the death sequence, `player` can not move until it ends.
+pub fn kill_player(commands: &mut Commands, player: Entity)
+{
+    commands.entity(player).insert((Dying(Timer::from_seconds(DEATH_DURATION, TimerMode::Once)), Immobile));
+}
+
+/// Makes hazards ignore `player` for a while.
+pub fn protect_player(commands: &mut Commands, player: Entity)
+{
+    commands.entity(player).insert(Invulnerable(Timer::from_seconds(INVULNERABILITY_DURATION, TimerMode::Once)));
+}
+
+pub fn reset_lives(mut commands: Commands)
+{
+    commands.insert_resource(Lives::default());
+}
+
+pub fn update_dying(
+    time: Res<Time>,
+    mut lives: ResMut<Lives>,
+    mut query: Query<&mut Dying>,
+    mut next_game_state: ResMut<NextState<GameState>>
+) {
+    for mut dying in query.iter_mut()
+    {
+        if dying.0.tick(time.delta()).just_finished()
+        {
+            lives.count = lives.count.saturating_sub(1);
+            lives.respawned = lives.count > 0;
+            next_game_state.set(if lives.count > 0 { GameState::Respawning } else { GameState::GameOver });
+        }
+    }
+}
+
+pub fn protect_respawned_player(
+    mut commands: Commands,
+    mut lives: ResMut<Lives>,
+    players: Query<Entity, Added<Player>>
+) {
+    for player in players.iter()
+    {
+        if lives.respawned
+        {
+            lives.respawned = false;
+            protect_player(&mut commands, player);
+        }
+    }
+}
+
+pub fn update_invulnerability(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Invulnerable, &mut Visibility)>
+) {
+    for (entity, mut invulnerable, mut visibility) in query.iter_mut()
+    {
+        if invulnerable.0.tick(time.delta()).finished()
+        {
+            commands.entity(entity).remove::<Invulnerable>();
+            *visibility = Visibility::Inherited;
+            continue;
+        }
+
+        let blink = (invulnerable.0.elapsed_secs() / BLINK_INTERVAL) as u32 & 1 == 0;
+        *visibility = if blink { Visibility::Hidden } else { Visibility::Inherited };
+    }
+}
+
+pub fn spawn_lives_label(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    lives: Res<Lives>
+) {
+    commands.spawn((
+        TextBundle::from_section(
+            format!("Жизни: {}", lives.count),
+            TextStyle {
+                font: game_assets.main_font.clone(),
+                font_size: 30.0,
+                color: Color::WHITE
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(5.0),
+            left: Val::Px(10.0),
+            ..Default::default()
+        }),
+        LivesLabel,
+        GameState::InGame
+    ));
+}
+
+pub fn update_lives_label(
+    lives: Res<Lives>,
+    mut labels: Query<&mut Text, With<LivesLabel>>
+) {
+    if !lives.is_changed()
+    {
+        return;
+    }
+
+    for mut text in labels.iter_mut()
+    {
+        text.sections[0].value = format!("Жизни: {}", lives.count);
+    }
+}
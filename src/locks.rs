@@ -0,0 +1,382 @@
+use bevy::asset::Handle;
+use bevy::core::Name;
+use bevy::ecs::change_detection::DetectChanges;
+use bevy::ecs::component::Component;
+use bevy::ecs::entity::Entity;
+use bevy::ecs::query::With;
+use bevy::ecs::query::Without;
+use bevy::ecs::system::Commands;
+use bevy::ecs::system::Query;
+use bevy::ecs::system::Res;
+use bevy::ecs::system::ResMut;
+use bevy::ecs::system::Resource;
+use bevy::hierarchy::DespawnRecursiveExt;
+use bevy::math::Vec2;
+use bevy::render::color::Color;
+use bevy::render::texture::Image;
+use bevy::render::view::Visibility;
+use bevy::sprite::Sprite;
+use bevy::sprite::SpriteBundle;
+use bevy::time::Time;
+use bevy::time::Timer;
+use bevy::time::TimerMode;
+use bevy::transform::components::Transform;
+use bevy::utils::HashSet;
+use bevy_xpbd_2d::components::Collider;
+use bevy_xpbd_2d::components::CollidingEntities;
+use bevy_xpbd_2d::components::CollisionLayers;
+use bevy_xpbd_2d::components::RigidBody;
+use bevy_xpbd_2d::components::Sensor;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::checkpoint::Checkpoint;
+use crate::level::CurrentLevelData;
+use crate::level::solid_layers;
+use crate::level::RectData;
+use crate::lives::Dying;
+use crate::player::Player;
+use crate::player::Layer;
+use crate::system::GameState;
+
+/// Size of a key pickup, keys are placed by their center.
+pub const KEY_SIZE: Vec2 = Vec2::new(10., 14.);
+
+/// Color of keys without a texture.
+pub const KEY_COLOR: Color = Color::rgb(0.3, 0.8, 1.0);
+
+/// What has to happen before a gate opens.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub enum LockData
+{
+    /// A quiz that names the gate is answered correctly.
+    #[default]
+    Quiz,
+    /// The player picked up the key with this id.
+    Key(String),
+    /// A switch sends this signal. The gate closes again when the signal stops.
+    Signal(String),
+    All(Vec<LockData>),
+    Any(Vec<LockData>)
+}
+
+impl LockData
+{
+    /// `|` separates alternatives and binds looser than `&`, which joins conditions that are all needed,
+    /// like `quiz & key:red | key:master`.
+    pub fn parse(text: &str) -> Option<Self>
+    {
+        let alternatives = text
+            .split('|')
+            .map(|alternative|
+            {
+                let conditions = alternative
+                    .split('&')
+                    .map(|condition| match condition.split_once(':').map(|(kind, id)| (kind.trim(), id.trim()))
+                    {
+                        Some(("key", id)) if !id.is_empty() => Some(LockData::Key(id.to_string())),
+                        Some(("signal", id)) if !id.is_empty() => Some(LockData::Signal(id.to_string())),
+                        None if condition.trim() == "quiz" => Some(LockData::Quiz),
+                        _ => None
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+
+                Some(if conditions.len() == 1 { conditions[0].clone() } else { LockData::All(conditions) })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(if alternatives.len() == 1 { alternatives[0].clone() } else { LockData::Any(alternatives) })
+    }
+
+    /// Every key the lock mentions.
+    pub fn keys(&self) -> Vec<&str>
+    {
+        match self
+        {
+            LockData::Key(id) => vec![id.as_str()],
+            LockData::All(locks) | LockData::Any(locks) => locks.iter().flat_map(|lock| lock.keys()).collect(),
+            _ => Vec::new()
+        }
+    }
+
+    /// Every signal the lock mentions, gates that wait for one can close again.
+    pub fn signals(&self) -> Vec<&str>
+    {
+        match self
+        {
+            LockData::Signal(id) => vec![id.as_str()],
+            LockData::All(locks) | LockData::Any(locks) => locks.iter().flat_map(|lock| lock.signals()).collect(),
+            _ => Vec::new()
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct GateData
+{
+    pub id: String,
+    pub texture: String,
+    pub rect: RectData,
+    #[serde(default)]
+    pub lock: LockData,
+
+    #[serde(skip)]
+    pub image: Handle<Image>
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct KeyData
+{
+    /// Gates name the keys they need with `LockData::Key`.
+    pub id: String,
+    pub x: f32,
+    pub y: f32,
+    #[serde(default)]
+    pub texture: String,
+
+    #[serde(skip)]
+    pub image: Handle<Image>
+}
+
+/// Identifies a spawned gate, it is despawned once its lock opens.
+#[derive(Component, Clone, Debug)]
+pub struct Gate(pub String);
+
+/// Everything the player has done towards opening the gates of the current room.
+#[derive(Resource, Clone, Debug, Default, PartialEq)]
+pub struct Unlocks
+{
+    pub keys: HashSet<String>,
+    /// Gates named by a correctly answered quiz.
+    pub quizzes: HashSet<String>,
+    /// Signals sent right now, by switches that are on or by answered quizzes.
+    pub signals: HashSet<String>,
+    /// Signals answered quizzes send for the rest of the room.
+    pub sent: HashSet<String>,
+    /// Levers that are switched on, by their index in the room.
+    pub levers: HashSet<usize>
+}
+
+impl Unlocks
+{
+    pub fn is_open(&self, gate: &str, lock: &LockData) -> bool
+    {
+        match lock
+        {
+            LockData::Quiz => self.quizzes.contains(gate),
+            LockData::Key(id) => self.keys.contains(id),
+            LockData::Signal(id) => self.signals.contains(id),
+            LockData::All(locks) => locks.iter().all(|lock| self.is_open(gate, lock)),
+            LockData::Any(locks) => locks.iter().any(|lock| self.is_open(gate, lock))
+        }
+    }
+}
+
+/// The condition a spawned gate waits for.
+#[derive(Component, Clone, Debug)]
+pub struct Lock(pub LockData);
+
+/// How long a gate opened by `QuizAction::Open` takes to fade away, in seconds.
+const FADE_DURATION: f32 = 0.6;
+
+/// The gate fades away once its lock opens instead of vanishing.
+#[derive(Component, Debug)]
+pub struct FadeOnOpen;
+
+/// An open gate on its way out, it no longer blocks anything.
+#[derive(Component, Debug)]
+pub struct Fading(pub Timer);
+
+/// A key pickup, the player keeps it until the room is left.
+#[derive(Component, Clone, Debug)]
+pub struct KeyItem(pub String);
+
+/// Starts the room with nothing unlocked, or with what was unlocked at the checkpoint.
+pub fn reset_unlocks(mut commands: Commands, current_level: CurrentLevelData)
+{
+    let unlocks = current_level.checkpoint().map(|checkpoint| checkpoint.unlocks.clone()).unwrap_or_default();
+    commands.insert_resource(unlocks);
+}
+
+pub fn collect_keys(
+    mut commands: Commands,
+    mut unlocks: ResMut<Unlocks>,
+    keys: Query<(Entity, &KeyItem, &CollidingEntities)>,
+    players: Query<(), (With<Player>, Without<Dying>)>
+) {
+    for (entity, key, colliding_entities) in keys.iter()
+    {
+        if colliding_entities.0.iter().any(|player| players.contains(*player))
+        {
+            unlocks.keys.insert(key.0.clone());
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Gates that are not on their way out yet.
+type StandingGates<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static Gate, &'static Lock, &'static mut CollisionLayers, &'static mut Visibility, Option<&'static FadeOnOpen>),
+    Without<Fading>
+>;
+
+/// Gates that wait for a signal are only hidden while it is sent, the others are gone for good.
+pub fn open_gates(
+    mut commands: Commands,
+    unlocks: Res<Unlocks>,
+    mut gates: StandingGates
+) {
+    if !unlocks.is_changed()
+    {
+        return;
+    }
+
+    for (entity, gate, lock, mut layers, mut visibility, fade) in gates.iter_mut()
+    {
+        let open = unlocks.is_open(&gate.0, &lock.0);
+        if lock.0.signals().is_empty()
+        {
+            if open && fade.is_some()
+            {
+                fade_gate(&mut commands, entity);
+            }
+            else if open
+            {
+                commands.entity(entity).despawn_recursive();
+            }
+
+            continue;
+        }
+
+        *layers = if open { CollisionLayers::none() } else { solid_layers() };
+        *visibility = if open { Visibility::Hidden } else { Visibility::Inherited };
+    }
+}
+
+/// The gate stops blocking the way right away and is gone once it faded.
+pub fn fade_gate(commands: &mut Commands, gate: Entity)
+{
+    commands.entity(gate).insert((CollisionLayers::none(), Fading(Timer::from_seconds(FADE_DURATION, TimerMode::Once))));
+}
+
+pub fn fade_gates(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut gates: Query<(Entity, &mut Fading, &mut Sprite)>
+) {
+    for (entity, mut fading, mut sprite) in gates.iter_mut()
+    {
+        if fading.0.tick(time.delta()).finished()
+        {
+            commands.entity(entity).despawn_recursive();
+        }
+        else
+        {
+            sprite.color.set_a(1. - fading.0.percent());
+        }
+    }
+}
+
+/// Gates opened before the checkpoint stay open.
+pub fn spawn_gates(commands: &mut Commands, gates: &[GateData], checkpoint: Option<&Checkpoint>)
+{
+    for gate in gates.iter()
+    {
+        if checkpoint.is_some_and(|checkpoint| checkpoint.opened_gates.contains(&gate.id))
+        {
+            continue;
+        }
+
+        commands.spawn((
+            SpriteBundle
+            {
+                texture: gate.image.clone(),
+                sprite: Sprite::default(),
+                transform: Transform::from_xyz(gate.rect.x, gate.rect.y, 0.),
+                ..Default::default()
+            },
+            RigidBody::Static,
+            Collider::cuboid(gate.rect.width, gate.rect.height),
+            solid_layers(),
+            Name::new(gate.id.clone()),
+            Gate(gate.id.clone()),
+            Lock(gate.lock.clone()),
+            GameState::InGame
+        ));
+    }
+}
+
+/// Keys picked up before the checkpoint are not spawned again.
+pub fn spawn_keys(commands: &mut Commands, keys: &[KeyData], checkpoint: Option<&Checkpoint>)
+{
+    for key in keys.iter()
+    {
+        if checkpoint.is_some_and(|checkpoint| checkpoint.unlocks.keys.contains(&key.id))
+        {
+            continue;
+        }
+
+        commands.spawn((
+            SpriteBundle
+            {
+                texture: key.image.clone(),
+                sprite: Sprite
+                {
+                    color: if key.texture.is_empty() { KEY_COLOR } else { Color::WHITE },
+                    custom_size: Some(KEY_SIZE),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(key.x, key.y, 0.5),
+                ..Default::default()
+            },
+            RigidBody::Static,
+            Collider::cuboid(KEY_SIZE.x, KEY_SIZE.y),
+            Sensor,
+            CollisionLayers::new([Layer::Interactable], [Layer::Player]),
+            Name::new(key.id.clone()),
+            KeyItem(key.id.clone()),
+            GameState::InGame
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn parses_locks()
+    {
+        assert_eq!(LockData::parse("quiz"), Some(LockData::Quiz));
+        assert_eq!(LockData::parse(" key : red "), Some(LockData::Key(String::from("red"))));
+        assert_eq!(LockData::parse("signal:lift"), Some(LockData::Signal(String::from("lift"))));
+        assert_eq!(
+            LockData::parse("quiz & key:red | key:master"),
+            Some(LockData::Any(vec![
+                LockData::All(vec![LockData::Quiz, LockData::Key(String::from("red"))]),
+                LockData::Key(String::from("master"))
+            ]))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_locks()
+    {
+        assert_eq!(LockData::parse(""), None);
+        assert_eq!(LockData::parse("key:"), None);
+        assert_eq!(LockData::parse("door:red"), None);
+        assert_eq!(LockData::parse("quiz &"), None);
+        assert_eq!(LockData::parse("quiz:1"), None);
+    }
+
+    #[test]
+    fn lists_keys_and_signals_of_a_lock()
+    {
+        let lock = LockData::parse("key:red & signal:lift | key:master").unwrap();
+        assert_eq!(lock.keys(), vec!["red", "master"]);
+        assert_eq!(lock.signals(), vec!["lift"]);
+    }
+}
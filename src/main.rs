@@ -1,70 +1,64 @@
 #![windows_subsystem = "windows"]
 
-mod system;
-mod animations;
-mod player;
-mod interactable;
-mod assets;
+use puzzle_game::animations::{update_animation, AnimatableLayer};
 
-use std::borrow::Borrow;
-use std::string;
-
-use animations::{update_animation, AnimatableLayer};
-
-use assets::{GameAssets, MenuAssets};
-use bevy::app::{AppExit, PluginGroup, Startup, StateTransition, Update};
-use bevy::asset::{AssetId, AssetServer, Assets, RecursiveDependencyLoadState};
+use puzzle_game::assets::{GameAssets, MenuAssets};
+use bevy::app::{PluginGroup, PostUpdate, Startup, StateTransition, Update};
+use bevy::asset::{AssetApp, Assets};
 use bevy::core_pipeline::core_2d::Camera2dBundle;
-use bevy::ecs::bundle::Bundle;
-use bevy::ecs::component::Component;
-use bevy::ecs::entity::Entity;
-use bevy::ecs::event::{EventReader, EventWriter};
-use bevy::ecs::query::{Changed, With, Without};
-use bevy::ecs::schedule::common_conditions::{resource_equals, resource_exists, run_once};
-use bevy::ecs::schedule::{Condition, NextState, OnEnter, OnExit, OnTransition, State, SystemSet};
-use bevy::ecs::system::{Commands, NonSend, Query, ResMut, RunSystemOnce};
-use bevy::ecs::world::World;
-use bevy::hierarchy::{BuildChildren, DespawnRecursiveExt};
+use bevy::ecs::schedule::common_conditions::resource_exists;
+use bevy::ecs::schedule::{apply_deferred, Condition, OnEnter, OnExit, State};
+use bevy::ecs::system::{Commands, NonSend};
+use bevy::hierarchy::BuildChildren;
 use bevy::input::keyboard::KeyCode;
-use bevy::input::mouse::{MouseButton, MouseButtonInput};
-use bevy::input::Input;
-use bevy::log::info;
-use bevy::math::{Vec2, Vec2Swizzles};
+use bevy::math::Vec2;
 use bevy::pbr::PointLightBundle;
-use bevy::prelude::{Deref, DerefMut};
-use bevy::reflect::Reflect;
 use bevy::render::color::Color;
 use bevy::render::texture::{Image, ImagePlugin};
-use bevy::scene::{DynamicScene, DynamicSceneBundle, Scene, SceneBundle, SceneSpawner};
-use bevy::sprite::{Anchor, Sprite, SpriteBundle, SpriteSheetBundle, TextureAtlasBuilder, TextureAtlasSprite};
-use bevy::text::{self, Text, TextAlignment, TextStyle};
+use bevy::sprite::{Anchor, Sprite, SpriteBundle, SpriteSheetBundle, TextureAtlasSprite};
+use bevy::text::{TextAlignment, TextStyle};
 use bevy::time::{Timer, TimerMode};
 use bevy::transform::components::Transform;
-use bevy::transform::TransformBundle;
+use bevy::transform::TransformSystem;
 use bevy::ui::node_bundles::{ButtonBundle, NodeBundle, TextBundle};
-use bevy::ui::widget::Button;
-use bevy::ui::{AlignItems, Display, GridPlacement, Interaction, JustifyContent, JustifySelf, PositionType, Style, UiRect, Val};
-use bevy::utils::hashbrown::Equivalent;
-use bevy::utils::HashMap;
-use bevy::window::{PrimaryWindow, Window, WindowPlugin, WindowResolution};
+use bevy::ui::{AlignItems, JustifyContent, JustifySelf, PositionType, Style, UiRect, Val};
+use bevy::window::{Window, WindowPlugin, WindowResolution};
 use bevy::winit::WinitWindows;
-use bevy::{app::App, asset::Handle, ecs::{schedule::{common_conditions::in_state, IntoSystemConfigs, States}, system::{Res, Resource}}, sprite::TextureAtlas, text::Font, DefaultPlugins};
-use bevy_asset_loader::{asset_collection::AssetCollection, loading_state::{config::ConfigureLoadingState, LoadingState, LoadingStateAppExt}, standard_dynamic_asset::StandardDynamicAssetCollection};
+use bevy::{app::App, ecs::{schedule::{common_conditions::in_state, IntoSystemConfigs}, system::Res}, DefaultPlugins};
+use bevy_asset_loader::{loading_state::{config::ConfigureLoadingState, LoadingState, LoadingStateAppExt}, standard_dynamic_asset::StandardDynamicAssetCollection};
 use bevy_pixel_camera::{PixelCameraPlugin, PixelViewport, PixelZoom};
-use bevy_xpbd_2d::components::{CoefficientCombine, Collider, CollidingEntities, CollisionLayers, LinearVelocity, LockedAxes, Restitution, RigidBody, Sensor};
+use bevy_xpbd_2d::components::{CoefficientCombine, Collider, CollisionLayers, LockedAxes, Restitution, RigidBody};
 use bevy_xpbd_2d::math::Vector;
 use bevy_xpbd_2d::plugins::debug::PhysicsDebugConfig;
-use bevy_xpbd_2d::plugins::spatial_query::{ShapeCaster, ShapeHits, SpatialQueryFilter};
+use bevy_xpbd_2d::plugins::spatial_query::{ShapeCaster, SpatialQueryFilter};
 use bevy_xpbd_2d::plugins::{PhysicsDebugPlugin, PhysicsPlugins};
-use bevy_xpbd_2d::prelude::PhysicsLayer;
+use bevy_xpbd_2d::PhysicsSet;
 use bevy_xpbd_2d::resources::Gravity;
-use interactable::{clear_quiz_buttons, interact_with_gobject, interact_with_menu_button, interact_with_quiz_button, make_uninteractable, update_player_interaction, GroundObject, Interactivity, MenuButtonAction, QuestionData, QuizButton, QuizButtonData};
+use puzzle_game::interactable::{clear_quiz_buttons, confirm_quiz_selection, interact_with_gobject, interact_with_menu_button, interact_with_quiz_button, show_explanations, type_quiz_answer, update_player_interaction, MenuButtonAction};
 use leafwing_input_manager::action_state::ActionState;
 use leafwing_input_manager::input_map::InputMap;
 use leafwing_input_manager::plugin::InputManagerPlugin;
-use leafwing_input_manager::{Actionlike, InputManagerBundle};
-use player::{update_player_movement, Layer, Player, PlayerAction};
-use system::{cleanup_after_state, next_level, CurrentLevel, GameState, QuizClear};
+use leafwing_input_manager::InputManagerBundle;
+use puzzle_game::level::{spawn_level, CurrentLevelData, LevelData, LevelLoader, VIEWPORT_SIZE};
+use puzzle_game::camera::{center_camera, follow_player, reset_camera, FollowCamera};
+use puzzle_game::campaign::{Campaign, CampaignLoader};
+use puzzle_game::checkpoint::{forget_checkpoint, respawn, touch_star_posts};
+use puzzle_game::lives::{protect_respawned_player, reset_lives, spawn_lives_label, update_dying, update_invulnerability, update_lives_label, Lives};
+use puzzle_game::platform::{carry_riders, move_platforms, trigger_platforms, Carried};
+use puzzle_game::enemy::{patrol_enemies, touch_enemies};
+use puzzle_game::rings::{add_ring_bonus, collect_rings, reset_rings, reset_score, spawn_rings_label, take_hits, update_rings_label, update_scattered_rings, Score};
+use puzzle_game::spring::{compress_springs, launch_players, update_launched};
+use puzzle_game::locks::{collect_keys, fade_gates, open_gates, reset_unlocks, Unlocks};
+use puzzle_game::triggers::{expire_messages, reset_triggers, run_triggers, QuizAnswered};
+use puzzle_game::switches::{color_switches, flip_levers, press_plates, send_signals, toggle_hazards};
+use puzzle_game::shuffle::{log_quiz_seed, reset_quiz_attempts, QuizRng};
+use puzzle_game::leaderboard::{clear_results, load_leaderboard, results_finished, show_results, spawn_timer_label, start_level_timer, tick_level_timer, update_results, update_timer_label, LevelStats};
+use puzzle_game::debug::{color_debug_colliders, draw_debug_overlay, toggle_debug_overlay, update_debug_labels, DebugOverlay};
+use puzzle_game::editor::{draw_editor_level, edit_with_keyboard, edit_with_mouse, interact_with_editor_panel, pan_editor_camera, setup_editor, store_editor_level, toggle_editor, update_editor_panel};
+use puzzle_game::tiled::TiledLoader;
+use puzzle_game::ldtk::{LdtkLoader, LdtkProject};
+use puzzle_game::player::{update_player_movement, Layer, Player, PlayerAction, GRAVITY, PLAYER_SIZE};
+use puzzle_game::system::{cleanup_after_state, next_level, restart_campaign, CurrentLevel, GameState, QuizClear};
 use winit::window::Icon;
 
 fn main()
@@ -72,7 +66,12 @@ fn main()
     App::new()
         .add_state::<GameState>()
         .insert_resource::<CurrentLevel>(CurrentLevel(1))
-        .insert_resource::<QuizClear>(QuizClear(false))
+        .insert_resource(Lives::default())
+        .insert_resource(Score::default())
+        .init_resource::<LevelStats>()
+        .insert_resource(QuizRng::from_args())
+        .init_resource::<Unlocks>()
+        .add_event::<QuizAnswered>()
         .add_plugins(DefaultPlugins
             .set(ImagePlugin::default_nearest())
             .set(WindowPlugin {
@@ -85,12 +84,22 @@ fn main()
             }))
         .add_plugins(PixelCameraPlugin)
         .add_plugins(InputManagerPlugin::<PlayerAction>::default())
+        .init_asset::<LevelData>()
+        .init_asset_loader::<LevelLoader>()
+        .init_asset_loader::<TiledLoader>()
+        .init_asset::<LdtkProject>()
+        .init_asset_loader::<LdtkLoader>()
+        .init_asset::<Campaign>()
+        .init_asset_loader::<CampaignLoader>()
         .add_plugins(PhysicsPlugins::default())
-            .insert_resource(Gravity(Vec2::NEG_Y * 100.0))
+            .add_plugins(PhysicsDebugPlugin::default())
+            .insert_resource(Gravity(Vec2::NEG_Y * GRAVITY))
             .insert_resource(PhysicsDebugConfig {
+                enabled: false,
                 aabb_color: Some(Color::WHITE),
                 ..Default::default()
             })
+            .insert_resource(DebugOverlay(false))
         .add_loading_state(
             LoadingState::new(GameState::PreLoading)
                 .continue_to_state(GameState::AssetLoading)
@@ -104,9 +113,12 @@ fn main()
                 .load_collection::<GameAssets>()
         )
         .add_systems(StateTransition, cleanup_after_state)
+        .add_systems(Startup, (load_leaderboard, log_quiz_seed))
         .add_systems(OnExit(GameState::PreLoading), (set_app_icon, camera_setup, preload))
-        .add_systems(OnEnter(GameState::MainMenu), setup_menu)
+        .add_systems(OnEnter(GameState::MainMenu), (setup_menu, center_camera, forget_checkpoint, reset_lives, reset_score))
+        .add_systems(OnEnter(GameState::Respawning), respawn)
         .add_systems(Update, update_animation)
+        .add_systems(Update, (toggle_debug_overlay, color_debug_colliders, update_debug_labels, draw_debug_overlay))
         .add_systems(Update,
             interact_with_menu_button.run_if(
                 in_state(GameState::MainMenu)
@@ -114,21 +126,77 @@ fn main()
                     .or_else(in_state(GameState::FullCompletion))
                     .or_else(in_state(GameState::InGame))
         ))
-        .add_systems(OnEnter(GameState::LevelCompleted), next_level)
-        .add_systems(OnEnter(GameState::InGame), spawn_player)
-        .add_systems(OnEnter(GameState::InGame), level_1.run_if(
-            resource_exists::<CurrentLevel>().and_then(resource_equals(CurrentLevel(1)))))
-            .add_systems(OnEnter(GameState::InGame), level_2.run_if(
-                resource_exists::<CurrentLevel>().and_then(resource_equals(CurrentLevel(2)))))
-        .add_systems(OnEnter(GameState::GameOver), setup_menu)
-        .add_systems(OnEnter(GameState::FullCompletion), setup_menu)
-        .add_systems(Update, (make_uninteractable, clear_quiz_buttons).chain().run_if(resource_exists::<QuizClear>()))
+        .add_systems(OnEnter(GameState::LevelCompleted), (add_ring_bonus, show_results).chain())
+        .add_systems(OnExit(GameState::LevelCompleted), clear_results)
+        .add_systems(Update,
+            (update_results, next_level.run_if(results_finished))
+                .chain()
+                .run_if(in_state(GameState::LevelCompleted)))
+        .add_systems(OnEnter(GameState::InGame), (spawn_level, spawn_player, reset_camera, spawn_lives_label, (reset_rings, spawn_rings_label).chain(), start_level_timer, reset_quiz_attempts, spawn_timer_label, reset_unlocks, reset_triggers))
+        .add_systems(OnEnter(GameState::GameOver), (setup_menu, center_camera, forget_checkpoint, reset_lives))
+        .add_systems(OnEnter(GameState::FullCompletion), (setup_menu, restart_campaign, center_camera, forget_checkpoint))
+        .add_systems(PostUpdate,
+            follow_player
+                .after(PhysicsSet::Sync)
+                .before(TransformSystem::TransformPropagate)
+                .run_if(in_state(GameState::InGame)))
+        .add_systems(Update, clear_quiz_buttons.run_if(resource_exists::<QuizClear>()))
         .add_systems(Update,
             (update_player_interaction,
                 update_player_movement,
+                trigger_platforms,
+                move_platforms,
+                carry_riders.before(update_player_movement),
+                patrol_enemies,
+                touch_enemies,
                 interact_with_gobject,
-                interact_with_quiz_button)
+                // Gates opened by a quiz must carry `FadeOnOpen` by the time `open_gates` looks at them
+                ((interact_with_quiz_button, confirm_quiz_selection, type_quiz_answer.after(update_player_interaction)), apply_deferred)
+                    .chain()
+                    .before(open_gates),
+                touch_star_posts,
+                update_dying,
+                protect_respawned_player,
+                update_invulnerability,
+                update_lives_label)
+                    .run_if(in_state(GameState::InGame)))
+        .add_systems(Update,
+            (collect_rings,
+                take_hits,
+                update_scattered_rings,
+                update_rings_label,
+                tick_level_timer,
+                update_timer_label,
+                launch_players.after(update_player_movement),
+                update_launched,
+                compress_springs,
+                collect_keys,
+                (flip_levers, press_plates, send_signals, (open_gates, toggle_hazards)).chain(),
+                color_switches,
+                fade_gates,
+                run_triggers.after(interact_with_quiz_button).after(confirm_quiz_selection).after(type_quiz_answer),
+                show_explanations.after(interact_with_quiz_button).after(confirm_quiz_selection).after(type_quiz_answer),
+                expire_messages)
                     .run_if(in_state(GameState::InGame)))
+        .add_systems(OnEnter(GameState::Editor), (setup_editor, forget_checkpoint))
+        .add_systems(OnExit(GameState::Editor), store_editor_level)
+        .add_systems(Update,
+            toggle_editor
+                .before(edit_with_keyboard)
+                .run_if(
+                    in_state(GameState::MainMenu)
+                        .or_else(in_state(GameState::InGame))
+                        .or_else(in_state(GameState::Editor))
+        ))
+        .add_systems(Update,
+            (pan_editor_camera,
+                edit_with_mouse,
+                edit_with_keyboard,
+                interact_with_editor_panel,
+                update_editor_panel,
+                draw_editor_level)
+                    .chain()
+                    .run_if(in_state(GameState::Editor)))
         .run();
 }
 
@@ -137,10 +205,11 @@ fn camera_setup(mut commands: Commands)
     commands.spawn((
         Camera2dBundle::default(),
         PixelZoom::FitSize {
-            width: 320,
-            height: 224,
+            width: VIEWPORT_SIZE.x as i32,
+            height: VIEWPORT_SIZE.y as i32,
         },
-        PixelViewport
+        PixelViewport,
+        FollowCamera::default()
     ));
 
     commands.spawn(
@@ -232,8 +301,7 @@ fn setup_menu(
     let text_style = TextStyle {
         font: game_assets.main_font.clone(),
         font_size: 80.0,
-        color: Color::BLACK.into(),
-        ..Default::default()
+        color: Color::BLACK
     };
 
     let label_style = Style {
@@ -287,7 +355,7 @@ fn setup_menu(
         .with_children(
             |parent|
             {
-                if (current_state != GameState::FullCompletion)
+                if current_state != GameState::FullCompletion
                 {
                     parent.spawn(
                         (
@@ -363,18 +431,17 @@ fn setup_menu(
 fn spawn_player(
     mut commands: Commands,
     image_assets: Res<GameAssets>,
-    current_level: Res<CurrentLevel>
+    current_level: CurrentLevelData
 ) {
+    let player_start = current_level.player_start();
+
     let query_filter = SpatialQueryFilter::new()
         .with_masks([Layer::Ground]);
 
     commands.spawn((
         SpriteSheetBundle
         {
-            transform: Transform::from_xyz(
-                if current_level.0 == 1 { 0. } else { -75. },
-                if current_level.0 == 1 { 0. } else { 100. },
-                1.),
+            transform: Transform::from_xyz(player_start.x, player_start.y, 1.),
             texture_atlas: image_assets.sonic.clone(),
             sprite: TextureAtlasSprite::default(),
             ..Default::default()
@@ -404,7 +471,7 @@ fn spawn_player(
             ),
         },
         RigidBody::Dynamic,
-        Collider::cuboid(14., 34.2),
+        Collider::cuboid(PLAYER_SIZE.x, PLAYER_SIZE.y),
 
         // Prevent the player from falling over
         LockedAxes::new().lock_rotation(),
@@ -423,527 +490,8 @@ fn spawn_player(
         // This controls how bouncy a rigid body is.
         Restitution::new(0.0).with_combine_rule(CoefficientCombine::Min),
         Player {},
+        Carried::default(),
         GameState::InGame,
         CollisionLayers::new([Layer::Player], [Layer::Ground, Layer::Enemy, Layer::Interactable])
     ));
 }
-
-fn level_1(
-    mut commands: Commands,
-    image_assets: Res<GameAssets>
-) {
-    // Background
-    commands.spawn((
-        SpriteBundle
-        {
-            texture: image_assets.level1.clone(),
-            transform: Transform::from_xyz(0., 0., -1.),
-            ..Default::default()
-        },
-        GameState::InGame
-    ));
-
-    // Ground
-    commands.spawn((
-        SpriteSheetBundle
-        {
-            visibility: bevy::render::view::Visibility::Hidden,
-            transform: Transform::from_xyz(-70., -25., 0.),
-            ..Default::default()
-        },
-        RigidBody::Static,
-        Collider::cuboid(150., 10.1),
-        GameState::InGame,
-        CollisionLayers::new([Layer::Ground], [Layer::Player, Layer::Enemy])
-    ));
-
-    // Ground 2
-    commands.spawn((
-        SpriteSheetBundle
-        {
-            visibility: bevy::render::view::Visibility::Hidden,
-            transform: Transform::from_xyz(-40., 30., 0.),
-            ..Default::default()
-        },
-        RigidBody::Static,
-        Collider::cuboid(90., 10.1),
-        GameState::InGame,
-        CollisionLayers::new([Layer::Ground], [Layer::Player, Layer::Enemy])
-    ));
-
-    // Ground 3
-    commands.spawn((
-        SpriteSheetBundle
-        {
-            visibility: bevy::render::view::Visibility::Hidden,
-            transform: Transform::from_xyz(-95., 20., 0.),
-            ..Default::default()
-        },
-        RigidBody::Static,
-        Collider::cuboid(20., 10.1),
-        GameState::InGame,
-        CollisionLayers::new([Layer::Ground], [Layer::Player, Layer::Enemy])
-    ));
-
-    // Uninteractable wall 1
-    commands.spawn((
-        SpriteSheetBundle
-        {
-            visibility: bevy::render::view::Visibility::Hidden,
-            transform: Transform::from_xyz(-150., 145., 0.),
-            ..Default::default()
-        },
-        RigidBody::Static,
-        Collider::cuboid(10.1, 350.),
-        CollisionLayers::new([Layer::Ground], [Layer::Player]),
-        GameState::InGame
-    ));
-
-    // Spike 0
-    commands.spawn((
-        SpriteSheetBundle
-        {
-            visibility: bevy::render::view::Visibility::Hidden,
-            transform: Transform::from_xyz(-75., 45., 0.),
-            ..Default::default()
-        },
-        AnimatableLayer
-        {
-            timer: Timer::from_seconds(0.125, TimerMode::Repeating),
-            animations: vec![(0, 10)],
-            current_animation: 0,
-            next_animation: 0,
-            flip_x: false,
-            repeat: true
-        },
-        RigidBody::Static,
-        Collider::cuboid(15.1, 15.),
-        CollisionLayers::new([Layer::Enemy], [Layer::Player]),
-        GameState::InGame,
-        GroundObject { next_game_state: GameState::GameOver }
-    ));
-
-    // Wall
-    let wall = commands.spawn((
-        SpriteBundle
-        {
-            texture: image_assets.gate0.clone(),
-            sprite: Sprite::default(),
-            transform: Transform::from_xyz(-10., 75., 0.),
-            ..Default::default()
-        },
-        RigidBody::Static,
-        Collider::cuboid(10.1, 75.),
-        CollisionLayers::new([Layer::Ground], [Layer::Player]),
-        GameState::InGame
-    )).id();
-
-    // Spikes 1
-    commands.spawn((
-        SpriteSheetBundle
-        {
-            visibility: bevy::render::view::Visibility::Hidden,
-            transform: Transform::from_xyz(40., -30., 0.),
-            ..Default::default()
-        },
-        AnimatableLayer
-        {
-            timer: Timer::from_seconds(0.125, TimerMode::Repeating),
-            animations: vec![(0, 10)],
-            current_animation: 0,
-            next_animation: 0,
-            flip_x: false,
-            repeat: true
-        },
-        RigidBody::Static,
-        Collider::cuboid(55., 15.),
-        CollisionLayers::new([Layer::Enemy], [Layer::Player]),
-        GameState::InGame,
-        GroundObject { next_game_state: GameState::GameOver }
-    ));
-
-    // Interactivity
-    commands.spawn((
-        SpriteSheetBundle
-        {
-            visibility: bevy::render::view::Visibility::Hidden,
-            transform: Transform::from_xyz(-35., 50., 0.),
-            ..Default::default()
-        },
-        AnimatableLayer
-        {
-            timer: Timer::from_seconds(0.125, TimerMode::Repeating),
-            animations: vec![(0, 10)],
-            current_animation: 0,
-            next_animation: 0,
-            flip_x: false,
-            repeat: true
-        },
-        RigidBody::Static,
-        Collider::cuboid(10.1, 10.1),
-        CollisionLayers::new([Layer::Interactable], [Layer::Player]),
-        Sensor,
-        GameState::InGame,
-
-        InputManagerBundle::<PlayerAction>
-        {
-            action_state: ActionState::default(),
-            input_map: InputMap::new([ (KeyCode::B, PlayerAction::Interact) ]),
-        },
-        Interactivity
-        {
-            can_interact: true,
-            is_interacting: false,
-            question: QuestionData
-            {
-                text: "Геометрический смысл производной функции".into(),
-                x: 850.,
-                y: 50.,
-            },
-            entity: Some(wall.clone()),
-            buttons: [
-                QuizButtonData { x: 700., y: 150., text: "Тангенс угла касательной".into(), is_correct: false },
-                QuizButtonData { x: 1000., y: 150., text: "Скорость изменения процесса".into(), is_correct: true },
-                QuizButtonData { x: 700., y: 250., text: "Ускорение процесса".into(), is_correct: false },
-                QuizButtonData { x: 1000., y: 250., text: "Дискриминант".into(), is_correct: false },
-            ]
-        }
-    ));
-
-    // Wall
-    let wall = commands.spawn((
-        SpriteBundle
-        {
-            texture: image_assets.gate1.clone(),
-            sprite: Sprite::default(),
-            transform: Transform::from_xyz(100., -25., 0.),
-            ..Default::default()
-        },
-        RigidBody::Static,
-        Collider::cuboid(50.1, 10.),
-        CollisionLayers::new([Layer::Ground], [Layer::Player]),
-        GameState::InGame
-    )).id();
-
-    // Uninteractable wall 2
-    commands.spawn((
-        SpriteSheetBundle
-        {
-            visibility: bevy::render::view::Visibility::Hidden,
-            transform: Transform::from_xyz(130., 0., 0.),
-            ..Default::default()
-        },
-        RigidBody::Static,
-        Collider::cuboid(10.1, 500.),
-        CollisionLayers::new([Layer::Ground], [Layer::Player]),
-        GameState::InGame
-    ));
-
-    // Interactivity 2
-    commands.spawn((
-        SpriteSheetBundle
-        {
-            visibility: bevy::render::view::Visibility::Hidden,
-            transform: Transform::from_xyz(100., -10., 0.),
-            ..Default::default()
-        },
-        AnimatableLayer
-        {
-            timer: Timer::from_seconds(0.125, TimerMode::Repeating),
-            animations: vec![(0, 10)],
-            current_animation: 0,
-            next_animation: 0,
-            flip_x: false,
-            repeat: true
-        },
-        RigidBody::Static,
-        Collider::cuboid(10.1, 10.1),
-        CollisionLayers::new([Layer::Interactable], [Layer::Player]),
-        Sensor,
-        GameState::InGame,
-
-        InputManagerBundle::<PlayerAction>
-        {
-            action_state: ActionState::default(),
-            input_map: InputMap::new([ (KeyCode::B, PlayerAction::Interact) ]),
-        },
-        Interactivity
-        {
-            can_interact: true,
-            is_interacting: false,
-            question: QuestionData
-            {
-                text: "Временные рамки Великой российской революции".into(),
-                x: 850.,
-                y: 50.,
-            },
-            entity: Some(wall.clone()),
-            buttons: [
-                QuizButtonData { x: 700., y: 150., text: "1905–1907".into(), is_correct: false },
-                QuizButtonData { x: 700., y: 250., text: "1941–1945".into(), is_correct: false },
-                QuizButtonData { x: 1000., y: 150., text: "1917–1922".into(), is_correct: true },
-                QuizButtonData { x: 1000., y: 250., text: "1812–1815".into(), is_correct: false },
-            ]
-        }
-    ));
-
-    // Spikes 2
-    commands.spawn((
-        SpriteSheetBundle
-        {
-            visibility: bevy::render::view::Visibility::Hidden,
-            transform: Transform::from_xyz(100., -100., 0.),
-            ..Default::default()
-        },
-        AnimatableLayer
-        {
-            timer: Timer::from_seconds(0.125, TimerMode::Repeating),
-            animations: vec![(0, 10)],
-            current_animation: 0,
-            next_animation: 0,
-            flip_x: false,
-            repeat: true
-        },
-        RigidBody::Static,
-        Collider::cuboid(45., 10.1),
-        CollisionLayers::new([Layer::Enemy], [Layer::Player]),
-        GameState::InGame,
-        GroundObject { next_game_state: GameState::GameOver }
-    ));
-
-    // Spikes 3
-    commands.spawn((
-        SpriteSheetBundle
-        {
-            visibility: bevy::render::view::Visibility::Hidden,
-            transform: Transform::from_xyz(105., -100., 0.),
-            ..Default::default()
-        },
-        AnimatableLayer
-        {
-            timer: Timer::from_seconds(0.125, TimerMode::Repeating),
-            animations: vec![(0, 10)],
-            current_animation: 0,
-            next_animation: 0,
-            flip_x: false,
-            repeat: true
-        },
-        RigidBody::Static,
-        Collider::cuboid(40., 10.1),
-        CollisionLayers::new([Layer::Enemy], [Layer::Player]),
-        GameState::InGame,
-        GroundObject { next_game_state: GameState::GameOver }
-    ));
-
-    // Level Goal
-    commands.spawn((
-        SpriteSheetBundle
-        {
-            visibility: bevy::render::view::Visibility::Hidden,
-            transform: Transform::from_xyz(65., -100., 0.),
-            ..Default::default()
-        },
-        AnimatableLayer
-        {
-            timer: Timer::from_seconds(0.125, TimerMode::Repeating),
-            animations: vec![(0, 10)],
-            current_animation: 0,
-            next_animation: 0,
-            flip_x: false,
-            repeat: true
-        },
-        RigidBody::Static,
-        Collider::cuboid(10.1, 10.1),
-        CollisionLayers::new([Layer::Enemy], [Layer::Player]),
-        GameState::InGame,
-        GroundObject { next_game_state: GameState::LevelCompleted }
-    ));
-}
-
-fn level_2(
-    mut commands: Commands,
-    image_assets: Res<GameAssets>
-) {
-    // Background
-    commands.spawn((
-        SpriteBundle
-        {
-            texture: image_assets.level2.clone(),
-            transform: Transform::from_xyz(0., 0., -1.),
-            ..Default::default()
-        },
-        GameState::InGame
-    ));
-
-    // Uninteractable wall 1
-    commands.spawn((
-        SpriteSheetBundle
-        {
-            visibility: bevy::render::view::Visibility::Hidden,
-            transform: Transform::from_xyz(-150., 0., 0.),
-            ..Default::default()
-        },
-        RigidBody::Static,
-        Collider::cuboid(10.1, 500.),
-        CollisionLayers::new([Layer::Ground], [Layer::Player]),
-        GameState::InGame
-    ));
-
-    // Uninteractable wall 2
-    commands.spawn((
-        SpriteSheetBundle
-        {
-            visibility: bevy::render::view::Visibility::Hidden,
-            transform: Transform::from_xyz(130., 0., 0.),
-            ..Default::default()
-        },
-        RigidBody::Static,
-        Collider::cuboid(10.1, 500.),
-        CollisionLayers::new([Layer::Ground], [Layer::Player]),
-        GameState::InGame
-    ));
-
-    let sets: [(f32, f32, f32, f32, f32, f32, f32, f32, f32, f32, f32, f32, f32, f32, f32, f32,
-        String, String, String, String, String, bool, bool, bool, bool, Handle<Image>); 4] = 
-    [
-        (-100., 70., 90., 10.1, 50., 70., 150., 10.1, -40., 70., 25.1, 10., 0., 85., 10.1, 10.1, 
-            "Как звали Раскольникова?".into(), "Родион".into(), "Ростислав".into(), "Ратибор".into(), "Руслан".into(), true, false, false, false, image_assets.gate2.clone()
-        ),
-        
-        (-70., 15., 150., 10.1, 85., 15., 80., 10.1, 25., 15., 35.1, 10., 100., 30., 10.1, 10.1, 
-            "Что не является хим. процессом?".into(), "Гидролиз".into(), "Преломление".into(), "Пиролиз".into(), "Галогенирование".into(), false, true, false, false, image_assets.gate3.clone()
-        ),
-        
-        (-85., -40., 120., 10.1, 65., -40., 120., 10.1, -10., -40., 30., 10., -60., -25., 10.1, 10.1,
-            "Какая столица не распологается в Азии?".into(), "Пекин".into(), "Скопье".into(), "Бангкок".into(), "Дакка".into(), false, true, false, false, image_assets.gate4.clone()
-        ),
-        
-        (-50., -95., 180., 10.1, 100., -95., 50., 10.1, 57.5, -95., 35., 10., -45., -80., 10.1, 10.1,
-            "Другое название низшей точки депрессии?".into(), "Минимум".into(), "Предел".into(), "Пик".into(), "Дно".into(), false, false, false, true, image_assets.gate5.clone()
-        )
-    ];
-
-    for (g_x1, g_y1, g_cx1, g_cy1,
-        g_x2, g_y2, g_cx2, g_cy2,
-        w_x, w_y, w_cx, w_cy,
-        i_x, i_y, i_cx, i_cy,
-        question, a1, a2, a3, a4,
-        c1, c2, c3, c4, gate
-    ) in sets.iter()
-    {
-        // Ground #1
-        commands.spawn((
-            SpriteBundle
-            {
-                visibility: bevy::render::view::Visibility::Hidden,
-                transform: Transform::from_xyz(*g_x1, *g_y1, 0.),
-                ..Default::default()
-            },
-            RigidBody::Static,
-            Collider::cuboid(*g_cx1, *g_cy1),
-            GameState::InGame,
-            CollisionLayers::new([Layer::Ground], [Layer::Player, Layer::Enemy])
-        ));
-
-        // Ground #2
-        commands.spawn((
-            SpriteBundle
-            {
-                visibility: bevy::render::view::Visibility::Hidden,
-                transform: Transform::from_xyz(*g_x2, *g_y2, 0.),
-                ..Default::default()
-            },
-            RigidBody::Static,
-            Collider::cuboid(*g_cx2, *g_cy2),
-            GameState::InGame,
-            CollisionLayers::new([Layer::Ground], [Layer::Player, Layer::Enemy])
-        ));
-
-        // Wall 1
-        let wall = commands.spawn((
-            SpriteBundle
-            {
-                sprite: Sprite::default(),
-                texture: gate.clone(),
-                transform: Transform::from_xyz(*w_x, *w_y, 0.),
-                ..Default::default()
-            },
-            RigidBody::Static,
-            Collider::cuboid(*w_cx, *w_cy),
-            CollisionLayers::new([Layer::Ground], [Layer::Player]),
-            GameState::InGame
-        )).id();
-
-        // Interactivity 1
-        commands.spawn((
-            SpriteBundle
-            {
-                visibility: bevy::render::view::Visibility::Hidden,
-                transform: Transform::from_xyz(*i_x, *i_y, 0.),
-                ..Default::default()
-            },
-            AnimatableLayer
-            {
-                timer: Timer::from_seconds(0.125, TimerMode::Repeating),
-                animations: vec![(0, 10)],
-                current_animation: 0,
-                next_animation: 0,
-                flip_x: false,
-                repeat: true
-            },
-            RigidBody::Static,
-            Collider::cuboid(*i_cx, *i_cy),
-            CollisionLayers::new([Layer::Interactable], [Layer::Player]),
-            Sensor,
-            GameState::InGame,
-
-            InputManagerBundle::<PlayerAction>
-            {
-                action_state: ActionState::default(),
-                input_map: InputMap::new([ (KeyCode::B, PlayerAction::Interact) ]),
-            },
-            Interactivity
-            {
-                can_interact: true,
-                is_interacting: false,
-                question: QuestionData
-                {
-                    text: question.into(),
-                    x: 850.,
-                    y: 50.,
-                },
-                entity: Some(wall.clone()),
-                buttons: [
-                    QuizButtonData { x: 700., y: 150., text: a1.into(), is_correct: c1.clone() },
-                    QuizButtonData { x: 1000., y: 150., text: a2.into(), is_correct: c2.clone() },
-                    QuizButtonData { x: 700., y: 250., text: a3.into(), is_correct: c3.clone() },
-                    QuizButtonData { x: 1000., y: 250., text: a4.into(), is_correct: c4.clone() },
-                ]
-            }
-        ));
-    }
-
-    // Level Goal
-    commands.spawn((
-        SpriteBundle
-        {
-            visibility: bevy::render::view::Visibility::Hidden,
-            transform: Transform::from_xyz(60., -105., 0.),
-            ..Default::default()
-        },
-        AnimatableLayer
-        {
-            timer: Timer::from_seconds(0.125, TimerMode::Repeating),
-            animations: vec![(0, 10)],
-            current_animation: 0,
-            next_animation: 0,
-            flip_x: false,
-            repeat: true
-        },
-        RigidBody::Static,
-        Collider::cuboid(10.1, 10.1),
-        CollisionLayers::new([Layer::Enemy], [Layer::Player]),
-        GameState::InGame,
-        GroundObject { next_game_state: GameState::LevelCompleted }
-    ));
-}
@@ -0,0 +1,258 @@
+use bevy::asset::Handle;
+use bevy::ecs::component::Component;
+use bevy::ecs::query::With;
+use bevy::ecs::query::Without;
+use bevy::ecs::system::Commands;
+use bevy::ecs::system::Query;
+use bevy::ecs::system::Res;
+use bevy::math::Vec2;
+use bevy::render::color::Color;
+use bevy::render::texture::Image;
+use bevy::sprite::Sprite;
+use bevy::sprite::SpriteBundle;
+use bevy::time::Time;
+use bevy::transform::components::Transform;
+use bevy_xpbd_2d::components::Collider;
+use bevy_xpbd_2d::components::CollisionLayers;
+use bevy_xpbd_2d::components::LinearVelocity;
+use bevy_xpbd_2d::components::Position;
+use bevy_xpbd_2d::components::RigidBody;
+use bevy_xpbd_2d::plugins::spatial_query::ShapeHits;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::level::PointData;
+use crate::level::RectData;
+use crate::locks::Unlocks;
+use crate::player::Player;
+use crate::player::JUMP_IMPULSE;
+use crate::player::Layer;
+use crate::spring::Launched;
+use crate::system::GameState;
+
+/// Speed of moving platforms imported from map editors without a `speed`, in units per second.
+pub const DEFAULT_PLATFORM_SPEED: f32 = 40.;
+
+/// Color of moving platforms without a texture.
+pub const PLATFORM_COLOR: Color = Color::rgb(0.55, 0.4, 0.25);
+
+/// How a moving platform goes through its stops.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub enum PlatformPath
+{
+    /// Halts at the last stop.
+    Linear,
+    /// Turns around at the first and the last stop.
+    #[default]
+    PingPong,
+    /// Goes from the last stop straight back to the first one.
+    Loop
+}
+
+impl PlatformPath
+{
+    /// Case, `_`, `-` and spaces do not matter, so `ping_pong`, `PingPong` and `Loop` all work.
+    pub fn parse(name: &str) -> Option<Self>
+    {
+        match name.to_lowercase().replace(['_', '-', ' '], "").as_str()
+        {
+            "linear" | "once" => Some(PlatformPath::Linear),
+            "pingpong" => Some(PlatformPath::PingPong),
+            "loop" | "looping" => Some(PlatformPath::Loop),
+            _ => None
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PlatformData
+{
+    /// Size of the platform and its first stop.
+    pub rect: RectData,
+    /// The stops after the first one, given by the platform's center.
+    pub path: Vec<PointData>,
+    /// In units per second.
+    pub speed: f32,
+    #[serde(default)]
+    pub mode: PlatformPath,
+    /// Waits at the first stop until the player steps on.
+    #[serde(default)]
+    pub triggered: bool,
+    /// Only moves while a switch sends this signal, empty to move all the time.
+    #[serde(default)]
+    pub signal: String,
+    #[serde(default)]
+    pub texture: String,
+
+    #[serde(skip)]
+    pub image: Handle<Image>
+}
+
+impl PlatformData
+{
+    /// Where the platform is at each of its stops.
+    pub fn stops(&self) -> impl Iterator<Item = RectData> + '_
+    {
+        std::iter::once(self.rect).chain(self.path.iter().map(|stop| RectData { x: stop.x, y: stop.y, ..self.rect }))
+    }
+}
+
+/// How close to a stop a platform has to get before heading for the next one.
+const ARRIVAL_DISTANCE: f32 = 0.5;
+
+/// A kinematic body that travels between stops, driven by its `LinearVelocity`.
+#[derive(Component, Debug)]
+pub struct MovingPlatform
+{
+    pub stops: Vec<Vec2>,
+    pub speed: f32,
+    pub path: PlatformPath,
+    /// Triggered platforms wait until the player stands on them.
+    pub waiting: bool,
+    /// Stands still while no switch sends this signal.
+    pub signal: Option<String>,
+    pub target: usize,
+    pub forward: bool
+}
+
+impl MovingPlatform
+{
+    pub fn new(platform: &PlatformData) -> Self
+    {
+        MovingPlatform
+        {
+            stops: platform.stops().map(|stop| Vec2::new(stop.x, stop.y)).collect(),
+            speed: platform.speed,
+            path: platform.mode,
+            waiting: platform.triggered,
+            signal: (!platform.signal.is_empty()).then(|| platform.signal.clone()),
+            target: 1,
+            forward: true
+        }
+    }
+
+    /// Picks the stop after `target`, `None` once a linear path is done.
+    fn next_stop(&mut self) -> Option<usize>
+    {
+        let last = self.stops.len() - 1;
+        match self.path
+        {
+            PlatformPath::Linear => (self.target < last).then_some(self.target + 1),
+            PlatformPath::Loop => Some((self.target + 1) % self.stops.len()),
+            PlatformPath::PingPong =>
+            {
+                if self.target == last || self.target == 0
+                {
+                    self.forward = self.target == 0;
+                }
+
+                Some(if self.forward { self.target + 1 } else { self.target - 1 })
+            }
+        }
+    }
+}
+
+/// Velocity the player got from the platform under them last frame.
+#[derive(Component, Debug, Default)]
+pub struct Carried(pub Vec2);
+
+pub fn trigger_platforms(
+    players: Query<&ShapeHits, With<Player>>,
+    mut platforms: Query<&mut MovingPlatform>
+) {
+    for hits in players.iter()
+    {
+        for hit in hits.iter()
+        {
+            if let Ok(mut platform) = platforms.get_mut(hit.entity)
+            {
+                platform.waiting = false;
+            }
+        }
+    }
+}
+
+pub fn move_platforms(
+    time: Res<Time>,
+    unlocks: Res<Unlocks>,
+    mut platforms: Query<(&mut MovingPlatform, &Position, &mut LinearVelocity)>
+) {
+    let delta = time.delta_seconds().max(f32::EPSILON);
+    for (mut platform, position, mut velocity) in platforms.iter_mut()
+    {
+        velocity.0 = Vec2::ZERO;
+        let switched_off = platform.signal.as_ref().is_some_and(|signal| !unlocks.signals.contains(signal));
+        if platform.waiting || switched_off || platform.stops.len() < 2
+        {
+            continue;
+        }
+
+        if platform.stops[platform.target].distance(position.0) < ARRIVAL_DISTANCE
+        {
+            match platform.next_stop()
+            {
+                Some(next) => platform.target = next,
+                None => continue
+            }
+        }
+
+        // Slow down for the last bit, so the platform does not overshoot its stop
+        let to_target = platform.stops[platform.target] - position.0;
+        velocity.0 = (to_target / delta).clamp_length_max(platform.speed);
+    }
+}
+
+/// Moves the player along with the platform they stand on. Friction alone lags behind
+/// and lets the `ShapeCaster` lose the ground when the platform goes down, so the
+/// platform's velocity is added outright. Jumping off keeps it.
+pub fn carry_riders(
+    platforms: Query<&LinearVelocity, (With<MovingPlatform>, Without<Player>)>,
+    mut riders: Query<(&ShapeHits, &mut LinearVelocity, &mut Carried, Option<&Launched>), With<Player>>
+) {
+    for (hits, mut velocity, mut carried, launched) in riders.iter_mut()
+    {
+        // Sticking to the platform would undo a spring launch
+        let platform = hits.iter().find_map(|hit| platforms.get(hit.entity).ok()).filter(|_| launched.is_none());
+        let Some(platform) = platform else
+        {
+            carried.0 = Vec2::ZERO;
+            continue;
+        };
+
+        velocity.x += platform.x - carried.0.x;
+
+        // Stick to the platform unless the player is jumping off it
+        if velocity.y - platform.y < JUMP_IMPULSE / 2.
+        {
+            velocity.y = platform.y;
+        }
+
+        carried.0 = platform.0;
+    }
+}
+
+pub fn spawn_platforms(commands: &mut Commands, platforms: &[PlatformData])
+{
+    for platform in platforms.iter()
+    {
+        commands.spawn((
+            SpriteBundle
+            {
+                texture: platform.image.clone(),
+                sprite: Sprite
+                {
+                    color: if platform.texture.is_empty() { PLATFORM_COLOR } else { Color::WHITE },
+                    custom_size: Some(Vec2::new(platform.rect.width, platform.rect.height)),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(platform.rect.x, platform.rect.y, 0.),
+                ..Default::default()
+            },
+            RigidBody::Kinematic,
+            Collider::cuboid(platform.rect.width, platform.rect.height),
+            CollisionLayers::new([Layer::Ground], [Layer::Player, Layer::Enemy, Layer::Ground, Layer::Interactable]),
+            MovingPlatform::new(platform),
+            GameState::InGame
+        ));
+    }
+}
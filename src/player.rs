@@ -2,6 +2,7 @@
 use bevy::ecs::component::Component;
 use bevy::ecs::query::With;
 use bevy::ecs::system::Query;
+use bevy::math::Vec2;
 use bevy::reflect::Reflect;
 use bevy_xpbd_2d::components::LinearVelocity;
 use bevy_xpbd_2d::plugins::spatial_query::ShapeHits;
@@ -10,6 +11,19 @@ use leafwing_input_manager::action_state::ActionState;
 use leafwing_input_manager::Actionlike;
 
 use crate::animations::AnimatableLayer;
+use crate::spring::Launched;
+
+/// Size of the player's collider.
+pub const PLAYER_SIZE: Vec2 = Vec2::new(14., 34.2);
+
+/// Vertical speed added when the player jumps.
+pub const JUMP_IMPULSE: f32 = 100.;
+
+/// Horizontal speed added every frame while a direction is held.
+pub const RUN_ACCELERATION: f32 = 1.2;
+
+/// Downwards acceleration of the physics world.
+pub const GRAVITY: f32 = 100.;
 
 #[derive(Component, Debug)]
 pub struct Player;
@@ -35,23 +49,35 @@ pub enum Layer
     Interactable
 }
 
+/// Everything that decides how the player moves.
+type Movement<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static ActionState<PlayerAction>,
+        &'static mut LinearVelocity,
+        &'static ShapeHits,
+        Option<&'static mut AnimatableLayer>,
+        Option<&'static Immobile>,
+        Option<&'static Launched>
+    ),
+    With<Player>
+>;
+
 pub fn update_player_movement(
-    mut query: Query<(
-        &ActionState<PlayerAction>,
-        &mut LinearVelocity,
-        &ShapeHits,
-        Option<&mut AnimatableLayer>,
-        Option<&Immobile>),
-    With<Player>>
+    mut query: Movement
 ) {
-    for (action_state, mut linear_velocity, ground_hits, mut animatable, immobile) in query.iter_mut()
+    for (action_state, mut linear_velocity, ground_hits, mut animatable, immobile, launched) in query.iter_mut()
     {
             if immobile.is_none()
             {
+                // Right after a spring launch the ground is still close enough to jump off
+                let on_ground = !ground_hits.is_empty() && launched.is_none();
+
                 let mut gonna_jump = false;
-                if action_state.just_pressed(PlayerAction::Jump) && !ground_hits.is_empty()
+                if action_state.just_pressed(PlayerAction::Jump) && on_ground
                 {
-                    linear_velocity.y += 100.0;
+                    linear_velocity.y += JUMP_IMPULSE;
                     gonna_jump = true;
 
                     if let Some(ref mut anim) = animatable
@@ -65,7 +91,7 @@ pub fn update_player_movement(
                 
                 if left || right
                 {
-                    linear_velocity.x += 1.2 * left.then_some(-1.).unwrap_or(1.);
+                    linear_velocity.x += RUN_ACCELERATION * if left { -1. } else { 1. };
 
                     if let Some(ref mut anim) = animatable
                     {
@@ -75,7 +101,7 @@ pub fn update_player_movement(
                 
                 if let Some(mut anim) = animatable
                 {
-                    if !gonna_jump && !ground_hits.is_empty()
+                    if !gonna_jump && on_ground
                     {
                         anim.next_animation = if linear_velocity.x.abs() < 0.5 && linear_velocity.y.abs() < 0.5 { 0 } else { 3 };
                     }
@@ -0,0 +1,332 @@
+use bevy::asset::LoadContext;
+use bevy::utils::HashMap;
+use bevy::utils::HashSet;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::answers::Normalization;
+use crate::answers::NumericAnswer;
+use crate::interactable::QuizData;
+use crate::interactable::QuizKind;
+use crate::level::LevelData;
+use crate::level::LevelLoaderError;
+
+/// One question of a bank, `correct` lists the right answers counting from 1.
+/// True/false questions may leave `answers` empty, free-text questions accept every answer and ignore `correct`,
+/// numeric questions give `numeric` or read the first answer as the number and its unit.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct QuestionEntry
+{
+    pub id: String,
+    pub prompt: String,
+    pub answers: Vec<String>,
+    pub correct: Vec<usize>,
+    #[serde(default)]
+    pub kind: QuizKind,
+    #[serde(default)]
+    pub normalize: Normalization,
+    #[serde(default)]
+    pub numeric: Option<NumericAnswer>,
+    #[serde(default)]
+    pub subject: String,
+    #[serde(default)]
+    pub difficulty: u8,
+    /// Shown after the question is answered.
+    #[serde(default)]
+    pub explanation: String
+}
+
+/// Questions kept apart from the rooms, loaded from a `*.questions.ron` file.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct QuestionBank
+{
+    pub questions: Vec<QuestionEntry>
+}
+
+/// Where a quiz takes its question from instead of its own `question` and `buttons`.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub enum QuestionRef
+{
+    Id { bank: String, id: String },
+    /// The first question of the subject, and of the difficulty if given, that no other quiz in the room uses.
+    Category
+    {
+        bank: String,
+        subject: String,
+        #[serde(default)]
+        difficulty: Option<u8>
+    }
+}
+
+impl QuestionRef
+{
+    /// Picks from `bank` by `<id>`, or by `subject:<subject>` optionally followed by `@<difficulty>`.
+    pub fn parse(bank: String, text: &str) -> Option<Self>
+    {
+        let text = text.trim();
+        match text.split_once(':')
+        {
+            Some(("subject", category)) =>
+            {
+                let (subject, difficulty) = match category.split_once('@')
+                {
+                    Some((subject, difficulty)) => (subject.trim(), Some(difficulty.trim().parse().ok()?)),
+                    None => (category.trim(), None)
+                };
+
+                (!subject.is_empty()).then(|| QuestionRef::Category { bank, subject: subject.to_string(), difficulty })
+            },
+            None if !text.is_empty() => Some(QuestionRef::Id { bank, id: text.to_string() }),
+            _ => None
+        }
+    }
+
+    pub fn bank(&self) -> &str
+    {
+        match self
+        {
+            QuestionRef::Id { bank, .. } | QuestionRef::Category { bank, .. } => bank
+        }
+    }
+}
+
+impl QuestionBank
+{
+    pub fn parse(bytes: &[u8]) -> Result<Self, LevelLoaderError>
+    {
+        Ok(ron::de::from_bytes::<QuestionBank>(bytes)?)
+    }
+
+    /// Finds the question `question` points to, skipping the ones in `used` when picking by category.
+    pub fn pick(&self, question: &QuestionRef, used: &HashSet<String>) -> Option<&QuestionEntry>
+    {
+        match question
+        {
+            QuestionRef::Id { id, .. } => self.questions.iter().find(|entry| entry.id == *id),
+            QuestionRef::Category { subject, difficulty, .. } => self.questions
+                .iter()
+                .filter(|entry| entry.subject == *subject && difficulty.is_none_or(|difficulty| entry.difficulty == difficulty))
+                .find(|entry| !used.contains(&entry.id))
+        }
+    }
+}
+
+impl LevelData
+{
+    /// Paths of the question banks the quizzes draw from.
+    pub fn question_banks(&self) -> Vec<String>
+    {
+        let mut banks: Vec<String> = self.quizzes
+            .iter()
+            .filter_map(|quiz| quiz.from_bank.as_ref())
+            .map(|question| question.bank().to_string())
+            .collect();
+
+        banks.sort();
+        banks.dedup();
+        banks
+    }
+
+    /// Replaces the question and answers of every quiz that draws from a bank,
+    /// `banks` holds every bank `question_banks` lists.
+    pub fn fill_questions(&mut self, banks: &HashMap<String, QuestionBank>) -> Result<(), String>
+    {
+        // Questions picked by id are never handed out again by category
+        let mut used: HashSet<String> = self.quizzes
+            .iter()
+            .filter_map(|quiz| match &quiz.from_bank
+            {
+                Some(QuestionRef::Id { id, .. }) => Some(id.clone()),
+                _ => None
+            })
+            .collect();
+
+        for quiz in self.quizzes.iter_mut()
+        {
+            let Some(question) = &quiz.from_bank else
+            {
+                continue;
+            };
+
+            let entry = banks
+                .get(question.bank())
+                .and_then(|bank| bank.pick(question, &used))
+                .ok_or_else(|| match question
+                {
+                    QuestionRef::Id { bank, id } => format!("question `{id}` is not in `{bank}`"),
+                    QuestionRef::Category { bank, subject, difficulty: Some(difficulty) } =>
+                        format!("`{bank}` has no unused question on `{subject}` at difficulty {difficulty}"),
+                    QuestionRef::Category { bank, subject, difficulty: None } => format!("`{bank}` has no unused question on `{subject}`")
+                })?;
+
+            used.insert(entry.id.clone());
+            let filled = QuizData::from_answers(quiz.rect, None, entry.kind, entry.prompt.clone(), &entry.answers, &entry.correct);
+            quiz.kind = filled.kind;
+            quiz.question = filled.question;
+            quiz.buttons = filled.buttons;
+            quiz.accepted = filled.accepted;
+            quiz.normalize = entry.normalize;
+            quiz.numeric = entry.numeric.clone().or(filled.numeric);
+            quiz.explanation = entry.explanation.clone();
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads the banks the room refers to through the asset server, so the room reloads when they change.
+pub async fn load_questions(level: &mut LevelData, load_context: &mut LoadContext<'_>) -> Result<(), LevelLoaderError>
+{
+    let mut banks = HashMap::new();
+    for path in level.question_banks()
+    {
+        let bytes = load_context.read_asset_bytes(path.clone()).await?;
+        banks.insert(path, QuestionBank::parse(&bytes)?);
+    }
+
+    level.fill_questions(&banks).map_err(LevelLoaderError::Question)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::level::RectData;
+
+    const BANK: &str = "questions/test.questions.ron";
+
+    fn bank() -> QuestionBank
+    {
+        QuestionBank::parse(r#"(
+            questions: [
+                (id: "sum", prompt: "2 + 2", answers: ["4", "5"], correct: [1], subject: "math", difficulty: 1),
+                (id: "product", prompt: "2 · 3", answers: ["5", "6"], correct: [2], subject: "math", difficulty: 2),
+                (id: "root", prompt: "√81", answers: [], correct: [], kind: Numeric, numeric: Some((value: 9.)), subject: "math", difficulty: 2),
+                (id: "name", prompt: "Как звали Раскольникова?", answers: ["Родион"], correct: [], kind: FreeText, subject: "literature",
+                    explanation: "Родион Романович"),
+            ],
+        )"#.as_bytes()).unwrap()
+    }
+
+    fn question(text: &str) -> QuestionRef
+    {
+        QuestionRef::parse(String::from(BANK), text).unwrap()
+    }
+
+    fn level(questions: &[&str]) -> LevelData
+    {
+        LevelData
+        {
+            quizzes: questions
+                .iter()
+                .map(|text| QuizData { rect: RectData::default(), gate: Some(String::from("door")), from_bank: Some(question(text)), ..Default::default() })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn banks() -> HashMap<String, QuestionBank>
+    {
+        HashMap::from([(String::from(BANK), bank())])
+    }
+
+    #[test]
+    fn parses_question_refs()
+    {
+        assert_eq!(question(" sum "), QuestionRef::Id { bank: String::from(BANK), id: String::from("sum") });
+        assert_eq!(question("subject:math"), QuestionRef::Category { bank: String::from(BANK), subject: String::from("math"), difficulty: None });
+        assert_eq!(question("subject: math @ 2"), QuestionRef::Category { bank: String::from(BANK), subject: String::from("math"), difficulty: Some(2) });
+        assert_eq!(question("sum").bank(), BANK);
+    }
+
+    #[test]
+    fn rejects_malformed_question_refs()
+    {
+        assert_eq!(QuestionRef::parse(String::from(BANK), ""), None);
+        assert_eq!(QuestionRef::parse(String::from(BANK), "subject:"), None);
+        assert_eq!(QuestionRef::parse(String::from(BANK), "subject:math@hard"), None);
+        assert_eq!(QuestionRef::parse(String::from(BANK), "topic:math"), None);
+    }
+
+    #[test]
+    fn picks_by_id_and_by_unused_category()
+    {
+        let bank = bank();
+        let mut used = HashSet::new();
+        assert_eq!(bank.pick(&question("product"), &used).map(|entry| entry.id.as_str()), Some("product"));
+        assert_eq!(bank.pick(&question("subject:math"), &used).map(|entry| entry.id.as_str()), Some("sum"));
+        assert_eq!(bank.pick(&question("subject:math@2"), &used).map(|entry| entry.id.as_str()), Some("product"));
+
+        used.insert(String::from("product"));
+        assert_eq!(bank.pick(&question("subject:math@2"), &used).map(|entry| entry.id.as_str()), Some("root"));
+
+        // Ids are picked even when used elsewhere
+        assert_eq!(bank.pick(&question("product"), &used).map(|entry| entry.id.as_str()), Some("product"));
+    }
+
+    #[test]
+    fn picks_nothing_for_unknown_ids_or_categories()
+    {
+        let bank = bank();
+        let used = HashSet::new();
+        assert!(bank.pick(&question("quotient"), &used).is_none());
+        assert!(bank.pick(&question("subject:history"), &used).is_none());
+        assert!(bank.pick(&question("subject:math@5"), &used).is_none());
+    }
+
+    #[test]
+    fn fills_quizzes_from_the_bank()
+    {
+        let mut level = level(&["subject:math@2", "product", "name"]);
+        level.fill_questions(&banks()).unwrap();
+
+        // `product` is taken by id, so the category falls through to the next question
+        assert_eq!(level.quizzes[0].question.text, "√81");
+        assert_eq!(level.quizzes[0].kind, QuizKind::Numeric);
+        assert_eq!(level.quizzes[0].numeric.as_ref().map(|numeric| numeric.value), Some(9.));
+
+        assert_eq!(level.quizzes[1].question.text, "2 · 3");
+        assert_eq!(level.quizzes[1].buttons.iter().map(|button| button.is_correct).collect::<Vec<_>>(), vec![false, true]);
+
+        assert_eq!(level.quizzes[2].kind, QuizKind::FreeText);
+        assert_eq!(level.quizzes[2].accepted, vec![String::from("Родион")]);
+        assert_eq!(level.quizzes[2].explanation, "Родион Романович");
+        assert_eq!(level.quizzes[2].gate, Some(String::from("door")));
+    }
+
+    #[test]
+    fn category_questions_are_not_handed_out_twice()
+    {
+        let mut level = level(&["subject:math@2", "subject:math@2"]);
+        level.fill_questions(&banks()).unwrap();
+        assert_eq!(level.quizzes[0].question.text, "2 · 3");
+        assert_eq!(level.quizzes[1].question.text, "√81");
+
+        let mut level = self::level(&["subject:math@2", "subject:math@2", "subject:math@2"]);
+        assert_eq!(level.fill_questions(&banks()), Err(format!("`{BANK}` has no unused question on `math` at difficulty 2")));
+    }
+
+    #[test]
+    fn reports_unknown_questions_and_banks()
+    {
+        assert_eq!(level(&["quotient"]).fill_questions(&banks()), Err(format!("question `quotient` is not in `{BANK}`")));
+        assert_eq!(level(&["subject:history"]).fill_questions(&banks()), Err(format!("`{BANK}` has no unused question on `history`")));
+        assert_eq!(level(&["sum"]).fill_questions(&HashMap::new()), Err(format!("question `sum` is not in `{BANK}`")));
+    }
+
+    #[test]
+    fn saves_the_bank_reference_instead_of_the_question()
+    {
+        let mut level = level(&["sum"]);
+        level.fill_questions(&banks()).unwrap();
+
+        let text = level.to_ron().unwrap();
+        assert!(!text.contains("2 + 2"), "{text}");
+
+        let mut saved = LevelData::parse(text.as_bytes()).unwrap();
+        assert_eq!(saved.quizzes[0].from_bank, Some(question("sum")));
+        saved.fill_questions(&banks()).unwrap();
+        assert_eq!(saved.quizzes[0].question.text, "2 + 2");
+        assert_eq!(saved.quizzes[0].gate, Some(String::from("door")));
+    }
+}
@@ -0,0 +1,685 @@
+use std::collections::VecDeque;
+
+use bevy::math::Vec2;
+use bevy::utils::HashSet;
+
+use crate::interactable::QuizAction;
+use crate::level::LevelData;
+use crate::level::RectData;
+use crate::locks::KEY_SIZE;
+use crate::locks::Unlocks;
+use crate::player::GRAVITY;
+use crate::player::JUMP_IMPULSE;
+use crate::player::PLAYER_SIZE;
+use crate::player::RUN_ACCELERATION;
+use crate::spring::SpringData;
+use crate::system::GameState;
+use crate::triggers::TriggerAction;
+use crate::triggers::TriggerEvent;
+
+/// `update_player_movement` accelerates once per frame, assume the game runs at 60 fps.
+const FRAMES_PER_SECOND: f32 = 60.;
+
+/// Distance between the standing spots tried along every surface.
+const STEP: f32 = 8.;
+
+const ARC_SAMPLES: usize = 24;
+
+/// Keeps a body resting on a surface from counting as overlapping it.
+const EPSILON: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Owner
+{
+    Ground(usize),
+    Wall,
+    Gate(usize),
+    Platform(usize),
+    Spring(usize)
+}
+
+/// A spot the player can stand on, given at the player's feet.
+#[derive(Clone, Copy, Debug)]
+struct Node
+{
+    owner: Owner,
+    feet: Vec2,
+    run_up: f32
+}
+
+/// Something the player only has to touch on the way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Touch
+{
+    Quiz(usize),
+    Key(usize),
+    Switch(usize),
+    Trigger(usize)
+}
+
+#[derive(Clone, Debug, Default)]
+struct Edge
+{
+    to: usize,
+    touched: Vec<Touch>
+}
+
+#[derive(Clone, Debug, Default)]
+struct Arc
+{
+    touched: Vec<Touch>,
+    goal: bool
+}
+
+struct Graph
+{
+    nodes: Vec<Node>,
+    edges: Vec<Vec<Edge>>,
+    goal: usize
+}
+
+/// Where the player starts falling: the player start, or a gate opened under their feet.
+struct Source
+{
+    feet: Vec2,
+    /// Spots the player jumps from to stand on the gate before it opens.
+    approaches: Vec<Vec2>
+}
+
+struct Room<'a>
+{
+    level: &'a LevelData,
+    open: Vec<bool>,
+    /// Signals of the switches reached so far, they decide which switched hazards are out.
+    signals: HashSet<String>,
+    /// A trigger that completes the level went off, the goal itself is not needed.
+    finished: bool
+}
+
+/// Simulates the jumps `update_player_movement` allows and reports rooms that cannot be
+/// finished: ground nobody can get to, quizzes out of reach, an unreachable goal and spots
+/// the player can fall into but never leave. Gates open as soon as their quiz or keys are reachable.
+/// Springs are spots the player lands on and is launched from right away. A switch counts as
+/// on once it is reached, which is a little generous for pressure plates that need a box.
+/// Triggers go off as soon as what they wait for can happen, timers right away.
+///
+/// Horizontal control is approximated by trying a few steering profiles, so the result is
+/// a hint for level designers rather than a proof.
+pub fn analyze_reachability(level: &LevelData) -> Vec<String>
+{
+    let mut room = Room { level, open: vec![false; level.gates.len()], signals: HashSet::new(), finished: false };
+    let mut sources = vec![Source
+    {
+        feet: Vec2::new(level.player_start.x, level.player_start.y - PLAYER_SIZE.y / 2.),
+        approaches: Vec::new()
+    }];
+
+    // Some spots can only be reached while a gate is still closed, so remember every stage
+    let mut ground_reached = vec![false; level.ground.len()];
+    let mut quizzes_reached = vec![false; level.quizzes.len()];
+    let mut triggers_entered = vec![false; level.triggers.len()];
+    let mut triggers_fired = vec![false; level.triggers.len()];
+    let mut forced: HashSet<String> = HashSet::new();
+    let mut unlocks = Unlocks::default();
+
+    loop
+    {
+        let graph = room.graph();
+        let (reached, touched) = room.explore(&graph, &sources);
+
+        for node in reached.iter().filter(|node| **node < graph.goal)
+        {
+            if let Owner::Ground(index) = graph.nodes[*node].owner
+            {
+                ground_reached[index] = true;
+            }
+        }
+
+        for touch in touched
+        {
+            match touch
+            {
+                Touch::Quiz(quiz) =>
+                {
+                    quizzes_reached[quiz] = true;
+                    for action in level.quizzes[quiz].all_actions()
+                    {
+                        match action
+                        {
+                            QuizAction::Unlock(id) | QuizAction::Open(id) => { unlocks.quizzes.insert(id); },
+                            QuizAction::Signal(id) => { unlocks.signals.insert(id); },
+                            QuizAction::Reward { .. } => {}
+                        }
+                    }
+                },
+                Touch::Key(key) => { unlocks.keys.insert(level.keys[key].id.clone()); },
+                Touch::Switch(switch) => { unlocks.signals.insert(level.switches[switch].signal.clone()); },
+                Touch::Trigger(trigger) => triggers_entered[trigger] = true
+            }
+        }
+
+        // Triggers may set each other off through signals
+        let mut fired_any = true;
+        while fired_any
+        {
+            fired_any = false;
+            for (index, trigger) in level.triggers.iter().enumerate()
+            {
+                let set_off = !triggers_fired[index] && match &trigger.when
+                {
+                    TriggerEvent::Enter(_) => triggers_entered[index],
+                    TriggerEvent::Answered { quiz, .. } => level.quizzes
+                        .iter()
+                        .zip(quizzes_reached.iter())
+                        .any(|(candidate, reached)| *reached && !quiz.is_empty() && candidate.id == *quiz),
+                    TriggerEvent::Signal { signal, .. } => unlocks.signals.contains(signal),
+                    TriggerEvent::Elapsed(_) => true
+                };
+
+                if !set_off
+                {
+                    continue;
+                }
+
+                triggers_fired[index] = true;
+                fired_any = true;
+                for action in trigger.then.iter()
+                {
+                    match action
+                    {
+                        TriggerAction::OpenGate(id) => { forced.insert(id.clone()); },
+                        TriggerAction::Signal(id) => { unlocks.signals.insert(id.clone()); },
+                        TriggerAction::State(GameState::LevelCompleted) => room.finished = true,
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        // New signals may retract hazards that were in the way
+        let mut changed = room.signals != unlocks.signals;
+        room.signals = unlocks.signals.clone();
+        for (gate, data) in level.gates.iter().enumerate()
+        {
+            if room.open[gate] || !(unlocks.is_open(&data.id, &data.lock) || forced.contains(&data.id))
+            {
+                continue;
+            }
+
+            // The last condition is met wherever the player stands then, including on the gate itself
+            for node in reached.iter().filter(|node| **node < graph.goal && graph.nodes[**node].owner == Owner::Gate(gate))
+            {
+                let approaches = graph.edges
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, edges)| edges.iter().any(|edge| edge.to == *node))
+                    .map(|(from, _)| graph.nodes[from].feet)
+                    .collect();
+
+                sources.push(Source { feet: graph.nodes[*node].feet, approaches });
+            }
+
+            room.open[gate] = true;
+            changed = true;
+        }
+
+        if !changed
+        {
+            return room.report(&graph, &sources, &ground_reached, &quizzes_reached, &unlocks);
+        }
+    }
+}
+
+impl<'a> Room<'a>
+{
+    fn solids(&self) -> impl Iterator<Item = (Owner, &RectData)>
+    {
+        self.level.ground.iter().enumerate().map(|(index, rect)| (Owner::Ground(index), rect))
+            .chain(self.level.walls.iter().map(|rect| (Owner::Wall, rect)))
+            .chain(self.level.gates
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| !self.open[*index])
+                .map(|(index, gate)| (Owner::Gate(index), &gate.rect)))
+    }
+
+    /// Moving platforms are stood on at each of their stops, but never block the way.
+    fn platform_stops(&self) -> impl Iterator<Item = (Owner, RectData)> + '_
+    {
+        self.level.platforms
+            .iter()
+            .enumerate()
+            .flat_map(|(index, platform)| platform.stops().map(move |stop| (Owner::Platform(index), stop)))
+    }
+
+    fn blocked(&self, body: &RectData) -> bool
+    {
+        self.solids().any(|(_, solid)| solid.overlaps(body))
+    }
+
+    fn deadly(&self, body: &RectData) -> bool
+    {
+        self.level.hazards.iter().any(|hazard| hazard.overlaps(body))
+            || self.level.switched_hazards
+                .iter()
+                .filter(|hazard| self.signals.contains(&hazard.signal) == hazard.inverted)
+                .any(|hazard| hazard.rect.overlaps(body))
+    }
+
+    fn touches_goal(&self, body: &RectData) -> bool
+    {
+        let touching = RectData { width: body.width + EPSILON * 4., height: body.height + EPSILON * 4., ..*body };
+        self.level.goal.overlaps(&touching)
+    }
+
+    fn touched(&self, body: &RectData) -> impl Iterator<Item = Touch> + '_
+    {
+        let body = *body;
+        let quizzes = self.level.quizzes
+            .iter()
+            .enumerate()
+            .filter(move |(_, quiz)| quiz.rect.overlaps(&body))
+            .map(|(index, _)| Touch::Quiz(index));
+
+        let keys = self.level.keys
+            .iter()
+            .enumerate()
+            .filter(move |(_, key)| RectData::centered(Vec2::new(key.x, key.y), KEY_SIZE).overlaps(&body))
+            .map(|(index, _)| Touch::Key(index));
+
+        let switches = self.level.switches
+            .iter()
+            .enumerate()
+            .filter(move |(_, switch)| switch.rect.overlaps(&body))
+            .map(|(index, _)| Touch::Switch(index));
+
+        let triggers = self.level.triggers
+            .iter()
+            .enumerate()
+            .filter(move |(_, trigger)| matches!(&trigger.when, TriggerEvent::Enter(area) if area.overlaps(&body)))
+            .map(|(index, _)| Touch::Trigger(index));
+
+        quizzes.chain(keys).chain(switches).chain(triggers)
+    }
+
+    fn nodes(&self) -> Vec<Node>
+    {
+        let mut nodes = Vec::new();
+        for (owner, solid) in self.solids().map(|(owner, solid)| (owner, *solid)).chain(self.platform_stops())
+        {
+            let (min, max) = (solid.min(), solid.max());
+            let count = ((max.x - min.x) / STEP).ceil().max(1.) as usize;
+            for index in 0..=count
+            {
+                let feet = Vec2::new(min.x + (max.x - min.x) * index as f32 / count as f32, max.y);
+                let body = body_at(feet);
+                if !self.blocked(&body) && !self.deadly(&body)
+                {
+                    nodes.push(Node { owner, feet, run_up: solid.width });
+                }
+            }
+        }
+
+        for (index, spring) in self.level.springs.iter().enumerate()
+        {
+            let feet = Vec2::new(spring.rect.x, spring.rect.max().y);
+            let body = body_at(feet);
+            if !self.blocked(&body) && !self.deadly(&body)
+            {
+                nodes.push(Node { owner: Owner::Spring(index), feet, run_up: 0. });
+            }
+        }
+
+        nodes
+    }
+
+    /// Follows a jump (or a fall when `launch` is zero) between two spots given at the feet.
+    fn arc(&self, from: Vec2, to: Vec2, launch: f32, run_up: f32) -> Option<Arc>
+    {
+        let discriminant = launch * launch + 2. * GRAVITY * (from.y - to.y);
+        if discriminant < 0.
+        {
+            return None;
+        }
+
+        let airtime = (launch + discriminant.sqrt()) / GRAVITY;
+        let acceleration = RUN_ACCELERATION * FRAMES_PER_SECOND;
+        let speed = (2. * acceleration * run_up).sqrt();
+        // Landing next to a spot is as good as landing on it, the player walks the rest
+        if (to.x - from.x).abs() > speed * airtime + acceleration * airtime * airtime / 2. + STEP
+        {
+            return None;
+        }
+
+        let profiles: [fn(f32) -> f32; 3] = [
+            |progress| progress,
+            |progress| progress * progress,
+            |progress| 1. - (1. - progress) * (1. - progress)
+        ];
+
+        'profiles: for profile in profiles
+        {
+            let mut arc = Arc::default();
+            for sample in 1..=ARC_SAMPLES
+            {
+                let progress = sample as f32 / ARC_SAMPLES as f32;
+                let time = airtime * progress;
+                let feet = Vec2::new(
+                    from.x + (to.x - from.x) * profile(progress),
+                    from.y + launch * time - GRAVITY * time * time / 2.
+                );
+
+                let body = body_at(feet);
+                if self.touches_goal(&body)
+                {
+                    arc.goal = true;
+                    return Some(arc);
+                }
+
+                if self.blocked(&body) || self.deadly(&body)
+                {
+                    continue 'profiles;
+                }
+
+                arc.touched.extend(self.touched(&body));
+            }
+
+            return Some(arc);
+        }
+
+        None
+    }
+
+    /// A spring only sends the player the way it points, its sideways push stands in for a run-up.
+    fn launch(&self, spring: &SpringData, from: Vec2, to: Vec2) -> Option<Arc>
+    {
+        if spring.impulse.x * (to.x - from.x) < 0.
+        {
+            return None;
+        }
+
+        let acceleration = RUN_ACCELERATION * FRAMES_PER_SECOND;
+        self.arc(from, to, spring.impulse.y, spring.impulse.x * spring.impulse.x / (2. * acceleration))
+    }
+
+    fn graph(&self) -> Graph
+    {
+        let nodes = self.nodes();
+        let goal = nodes.len();
+        let goal_top = Vec2::new(self.level.goal.x, self.level.goal.max().y);
+        let mut edges = vec![Vec::new(); nodes.len()];
+
+        for (from, node) in nodes.iter().enumerate()
+        {
+            let targets = nodes.iter().map(|target| target.feet).chain(std::iter::once(goal_top));
+            for (to, target) in targets.enumerate().filter(|(to, _)| *to != from)
+            {
+                // Walking off a ledge needs no jump, which matters under low ceilings
+                let arc = match node.owner
+                {
+                    Owner::Spring(index) => self.launch(&self.level.springs[index], node.feet, target),
+                    _ => self.arc(node.feet, target, JUMP_IMPULSE, node.run_up)
+                        .or_else(|| self.arc(node.feet, target, 0., node.run_up))
+                };
+
+                if let Some(arc) = arc
+                {
+                    edges[from].push(Edge { to: if arc.goal { goal } else { to }, touched: arc.touched });
+                }
+            }
+
+            // Riding a platform takes the player to every other stop of it
+            if let Owner::Platform(_) = node.owner
+            {
+                for (to, _) in nodes.iter().enumerate().filter(|(to, other)| *to != from && other.owner == node.owner)
+                {
+                    edges[from].push(Edge { to, touched: Vec::new() });
+                }
+            }
+
+            if let Owner::Spring(_) = node.owner
+            {
+                continue;
+            }
+
+            // Neighbouring surfaces at the same height can be walked across
+            let next = nodes
+                .iter()
+                .enumerate()
+                .filter(|(_, other)| !matches!(other.owner, Owner::Spring(_)))
+                .filter(|(_, other)| (other.feet.y - node.feet.y).abs() < EPSILON && other.feet.x > node.feet.x)
+                .min_by(|(_, a), (_, b)| a.feet.x.total_cmp(&b.feet.x))
+                .filter(|(_, other)| other.feet.x - node.feet.x <= PLAYER_SIZE.x);
+
+            if let Some((to, other)) = next
+            {
+                let corridor = RectData::centered(
+                    Vec2::new((node.feet.x + other.feet.x) / 2., node.feet.y + PLAYER_SIZE.y / 2.),
+                    Vec2::new(other.feet.x - node.feet.x + PLAYER_SIZE.x, PLAYER_SIZE.y) - Vec2::splat(EPSILON * 2.)
+                );
+
+                if !self.blocked(&corridor) && !self.deadly(&corridor)
+                {
+                    let touched: Vec<Touch> = self.touched(&corridor).collect();
+                    edges[from].push(Edge { to, touched: touched.clone() });
+                    edges[to].push(Edge { to: from, touched });
+                }
+            }
+        }
+
+        Graph { nodes, edges, goal }
+    }
+
+    /// Returns the reached nodes (the goal counts as the last node) and what was touched on the way.
+    fn explore(&self, graph: &Graph, sources: &[Source]) -> (Vec<usize>, Vec<Touch>)
+    {
+        let mut visited = vec![false; graph.goal + 1];
+        let mut touched = Vec::new();
+        let mut queue = VecDeque::new();
+
+        for source in sources.iter()
+        {
+            for edge in self.landings(graph, source.feet)
+            {
+                touched.extend(edge.touched);
+                queue.push_back(edge.to);
+            }
+        }
+
+        let mut order = Vec::new();
+        while let Some(node) = queue.pop_front()
+        {
+            if visited[node]
+            {
+                continue;
+            }
+
+            visited[node] = true;
+            order.push(node);
+            if node == graph.goal
+            {
+                continue;
+            }
+
+            touched.extend(self.touched(&body_at(graph.nodes[node].feet)));
+            for edge in graph.edges[node].iter()
+            {
+                touched.extend(edge.touched.iter().copied());
+                queue.push_back(edge.to);
+            }
+        }
+
+        touched.sort_unstable();
+        touched.dedup();
+        (order, touched)
+    }
+
+    /// Falls straight down from `feet`, steering towards every spot in turn.
+    fn landings(&self, graph: &Graph, feet: Vec2) -> Vec<Edge>
+    {
+        let goal_top = Vec2::new(self.level.goal.x, self.level.goal.max().y);
+        graph.nodes
+            .iter()
+            .map(|node| node.feet)
+            .chain(std::iter::once(goal_top))
+            .enumerate()
+            .filter_map(|(to, target)| self.arc(feet, target, 0., 0.)
+                .map(|arc| Edge { to: if arc.goal { graph.goal } else { to }, touched: arc.touched }))
+            .collect()
+    }
+
+    fn report(&self, graph: &Graph, sources: &[Source], ground_reached: &[bool], quizzes_reached: &[bool], unlocks: &Unlocks) -> Vec<String>
+    {
+        let (reached, _) = self.explore(graph, sources);
+        let mut problems = Vec::new();
+
+        // Walk the edges backwards from the goal to find spots that can still finish the room,
+        // opened gates are extra nodes after the goal that are entered from their approaches
+        let mut incoming = vec![Vec::new(); graph.goal + 1 + sources.len()];
+        for (from, edges) in graph.edges.iter().enumerate()
+        {
+            for edge in edges.iter()
+            {
+                incoming[edge.to].push(from);
+            }
+        }
+
+        for (index, source) in sources.iter().enumerate()
+        {
+            let virtual_node = graph.goal + 1 + index;
+            for edge in self.landings(graph, source.feet)
+            {
+                incoming[edge.to].push(virtual_node);
+            }
+
+            for (from, node) in graph.nodes.iter().enumerate()
+            {
+                if source.approaches.iter().any(|approach| approach.distance(node.feet) < EPSILON)
+                {
+                    incoming[virtual_node].push(from);
+                }
+            }
+        }
+
+        let mut can_finish = vec![false; incoming.len()];
+        let mut queue = VecDeque::from([graph.goal]);
+        while let Some(node) = queue.pop_front()
+        {
+            if !can_finish[node]
+            {
+                can_finish[node] = true;
+                queue.extend(incoming[node].iter().copied());
+            }
+        }
+
+        for (index, ground) in self.level.ground.iter().enumerate()
+        {
+            let trapped = reached
+                .iter()
+                .any(|node| *node < graph.goal && graph.nodes[*node].owner == Owner::Ground(index) && !can_finish[*node]);
+
+            if !ground_reached[index]
+            {
+                problems.push(format!("ground at ({}, {}) cannot be reached", ground.x, ground.y));
+            }
+            else if reached.contains(&graph.goal) && trapped
+            {
+                problems.push(format!("ground at ({}, {}) traps the player away from the goal", ground.x, ground.y));
+            }
+        }
+
+        for (index, quiz) in self.level.quizzes.iter().enumerate()
+        {
+            if !quizzes_reached[index]
+            {
+                problems.push(format!("quiz \"{}\" cannot be reached", quiz.question.text));
+            }
+        }
+
+        for key in self.level.keys.iter().filter(|key| !unlocks.keys.contains(&key.id))
+        {
+            problems.push(format!("key `{}` cannot be reached", key.id));
+        }
+
+        if !reached.contains(&graph.goal) && !self.finished
+        {
+            problems.push(String::from("goal cannot be reached even with every reachable gate open"));
+        }
+
+        problems
+    }
+}
+
+fn body_at(feet: Vec2) -> RectData
+{
+    RectData::centered(feet + Vec2::Y * PLAYER_SIZE.y / 2., PLAYER_SIZE - Vec2::splat(EPSILON * 2.))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::interactable::QuizData;
+    use crate::interactable::QuizKind;
+    use crate::level::PointData;
+    use crate::locks::GateData;
+    use crate::locks::LockData;
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> RectData
+    {
+        RectData { x, y, width, height }
+    }
+
+    /// A floor to walk along from the start to the goal, behind a gate a quiz opens.
+    fn room() -> LevelData
+    {
+        LevelData
+        {
+            player_start: PointData { x: -100., y: 0. },
+            ground: vec![rect(0., -40., 300., 10.)],
+            gates: vec![GateData { id: String::from("door"), rect: rect(0., 40., 10., 150.), ..Default::default() }],
+            quizzes: vec![QuizData::from_answers(
+                rect(-50., -25., 10., 10.),
+                Some(String::from("door")),
+                QuizKind::Single,
+                String::from("2 + 2"),
+                &[String::from("4"), String::from("5")],
+                &[1]
+            )],
+            goal: rect(100., -30., 10., 10.),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn walkable_room_can_be_finished()
+    {
+        assert_eq!(analyze_reachability(&room()), Vec::<String>::new());
+    }
+
+    #[test]
+    fn reports_goal_out_of_jumping_reach()
+    {
+        let mut level = room();
+        level.goal = rect(100., 100., 10., 10.);
+        assert_eq!(analyze_reachability(&level), vec![String::from("goal cannot be reached even with every reachable gate open")]);
+    }
+
+    #[test]
+    fn reports_goal_behind_a_gate_that_never_opens()
+    {
+        let mut level = room();
+        level.gates[0].lock = LockData::Key(String::from("red"));
+        assert_eq!(analyze_reachability(&level), vec![String::from("goal cannot be reached even with every reachable gate open")]);
+    }
+
+    #[test]
+    fn reports_quiz_out_of_jumping_reach()
+    {
+        let mut level = room();
+        level.quizzes[0].rect.y = 100.;
+        let problems = analyze_reachability(&level);
+        assert!(problems.contains(&String::from("quiz \"2 + 2\" cannot be reached")), "{problems:?}");
+        assert!(problems.contains(&String::from("goal cannot be reached even with every reachable gate open")), "{problems:?}");
+    }
+}
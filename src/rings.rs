@@ -0,0 +1,278 @@
+use std::f32::consts::PI;
+
+use bevy::ecs::change_detection::DetectChanges;
+use bevy::ecs::component::Component;
+use bevy::ecs::entity::Entity;
+use bevy::ecs::query::With;
+use bevy::ecs::query::Without;
+use bevy::ecs::system::Commands;
+use bevy::ecs::system::Query;
+use bevy::ecs::system::Res;
+use bevy::ecs::system::ResMut;
+use bevy::ecs::system::Resource;
+use bevy::hierarchy::BuildChildren;
+use bevy::hierarchy::DespawnRecursiveExt;
+use bevy::hierarchy::Parent;
+use bevy::math::Vec2;
+use bevy::render::color::Color;
+use bevy::sprite::Sprite;
+use bevy::sprite::SpriteBundle;
+use bevy::text::Text;
+use bevy::text::TextStyle;
+use bevy::time::Time;
+use bevy::time::Timer;
+use bevy::time::TimerMode;
+use bevy::transform::components::Transform;
+use bevy::transform::TransformBundle;
+use bevy::ui::node_bundles::TextBundle;
+use bevy::ui::PositionType;
+use bevy::ui::Style;
+use bevy::ui::Val;
+use bevy_xpbd_2d::components::CollidingEntities;
+use bevy_xpbd_2d::components::Collider;
+use bevy_xpbd_2d::components::CollisionLayers;
+use bevy_xpbd_2d::components::LinearVelocity;
+use bevy_xpbd_2d::components::Restitution;
+use bevy_xpbd_2d::components::RigidBody;
+use bevy_xpbd_2d::components::Sensor;
+
+use crate::assets::GameAssets;
+use crate::lives::kill_player;
+use crate::lives::protect_player;
+use crate::lives::Dying;
+use crate::lives::Hurt;
+use crate::player::Layer;
+use crate::player::Player;
+use crate::system::GameState;
+
+/// Size of a ring pickup, rings are placed by their center.
+pub const RING_SIZE: Vec2 = Vec2::new(12., 12.);
+
+pub const RING_COLOR: Color = Color::rgb(1.0, 0.84, 0.0);
+
+/// Points for every ring the player holds when reaching the goal.
+pub const RING_BONUS: u32 = 100;
+
+/// Losing more rings than this only scatters this many.
+const MAX_SCATTERED_RINGS: u32 = 16;
+
+const SCATTER_SPEED: f32 = 80.;
+
+/// How long scattered rings stay around, in seconds.
+const SCATTERED_RING_LIFETIME: f32 = 4.;
+
+/// Scattered rings can not be picked up again right away, in seconds.
+const PICKUP_DELAY: f32 = 0.5;
+
+/// Rings held in the current room and the score of the whole run.
+#[derive(Resource, Debug, Default)]
+pub struct Score
+{
+    pub rings: u32,
+    pub total: u32
+}
+
+/// A pickup, either placed in the room or a sensor child of a `ScatteredRing`.
+#[derive(Component, Debug)]
+pub struct Ring;
+
+/// A ring knocked out of the player, it bounces around until the timer runs out.
+#[derive(Component, Debug)]
+pub struct ScatteredRing(pub Timer);
+
+#[derive(Component, Debug)]
+pub struct RingsLabel;
+
+/// A pickup placed in the room, or dropped there by a quiz.
+pub fn spawn_ring(commands: &mut Commands, position: Vec2) -> Entity
+{
+    commands.spawn((
+        SpriteBundle
+        {
+            sprite: Sprite
+            {
+                color: RING_COLOR,
+                custom_size: Some(RING_SIZE),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(position.x, position.y, 0.5),
+            ..Default::default()
+        },
+        RigidBody::Static,
+        Collider::ball(RING_SIZE.x / 2.),
+        Sensor,
+        CollisionLayers::new([Layer::Interactable], [Layer::Player]),
+        Ring,
+        GameState::InGame
+    )).id()
+}
+
+/// Rewards are laid out in a row centered on `center`.
+pub fn spawn_ring_row(commands: &mut Commands, center: Vec2, rings: u32)
+{
+    let spacing = RING_SIZE.x * 1.5;
+    let first = center.x - spacing * (rings as f32 - 1.) / 2.;
+    for ring in 0..rings
+    {
+        spawn_ring(commands, Vec2::new(first + spacing * ring as f32, center.y));
+    }
+}
+
+pub fn reset_score(mut commands: Commands)
+{
+    commands.insert_resource(Score::default());
+}
+
+/// Rings do not carry over from one room, or one attempt, to the next.
+pub fn reset_rings(mut score: ResMut<Score>)
+{
+    score.rings = 0;
+}
+
+pub fn collect_rings(
+    mut commands: Commands,
+    mut score: ResMut<Score>,
+    rings: Query<(Entity, &CollidingEntities, Option<&Parent>), With<Ring>>,
+    scattered: Query<&ScatteredRing>,
+    players: Query<(), (With<Player>, Without<Dying>)>
+) {
+    for (entity, colliding_entities, parent) in rings.iter()
+    {
+        if !colliding_entities.0.iter().any(|player| players.contains(*player))
+        {
+            continue;
+        }
+
+        let ring = parent.map_or(entity, |parent| parent.get());
+        if scattered.get(ring).is_ok_and(|scattered| scattered.0.elapsed_secs() < PICKUP_DELAY)
+        {
+            continue;
+        }
+
+        score.rings += 1;
+        commands.entity(ring).despawn_recursive();
+    }
+}
+
+/// A hit scatters the player's rings, only a hit without rings costs a life.
+pub fn take_hits(
+    mut commands: Commands,
+    mut score: ResMut<Score>,
+    players: Query<(Entity, &Transform), With<Hurt>>
+) {
+    for (player, transform) in players.iter()
+    {
+        commands.entity(player).remove::<Hurt>();
+        if score.rings == 0
+        {
+            kill_player(&mut commands, player);
+            continue;
+        }
+
+        // Fan the rings out over the player's head
+        let count = score.rings.min(MAX_SCATTERED_RINGS);
+        for index in 0..count
+        {
+            let angle = PI * (index as f32 + 0.5) / count as f32;
+            scatter_ring(&mut commands, transform.translation.truncate(), Vec2::from_angle(angle) * SCATTER_SPEED);
+        }
+
+        score.rings = 0;
+        protect_player(&mut commands, player);
+    }
+}
+
+/// The body bounces off the ground, the sensor child is what the player touches.
+fn scatter_ring(commands: &mut Commands, position: Vec2, velocity: Vec2)
+{
+    commands.spawn((
+        SpriteBundle
+        {
+            sprite: Sprite
+            {
+                color: RING_COLOR,
+                custom_size: Some(RING_SIZE),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(position.x, position.y, 0.5),
+            ..Default::default()
+        },
+        RigidBody::Dynamic,
+        Collider::ball(RING_SIZE.x / 2.),
+        CollisionLayers::new([Layer::Interactable], [Layer::Ground]),
+        LinearVelocity(velocity),
+        Restitution::new(0.6),
+        ScatteredRing(Timer::from_seconds(SCATTERED_RING_LIFETIME, TimerMode::Once)),
+        GameState::InGame
+    ))
+    .with_children(|parent|
+    {
+        parent.spawn((
+            TransformBundle::default(),
+            Collider::ball(RING_SIZE.x / 2.),
+            Sensor,
+            CollisionLayers::new([Layer::Interactable], [Layer::Player]),
+            Ring
+        ));
+    });
+}
+
+pub fn update_scattered_rings(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut rings: Query<(Entity, &mut ScatteredRing)>
+) {
+    for (entity, mut ring) in rings.iter_mut()
+    {
+        if ring.0.tick(time.delta()).finished()
+        {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub fn spawn_rings_label(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    score: Res<Score>
+) {
+    commands.spawn((
+        TextBundle::from_section(
+            format!("Кольца: {}", score.rings),
+            TextStyle {
+                font: game_assets.main_font.clone(),
+                font_size: 30.0,
+                color: RING_COLOR
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(40.0),
+            left: Val::Px(10.0),
+            ..Default::default()
+        }),
+        RingsLabel,
+        GameState::InGame
+    ));
+}
+
+pub fn update_rings_label(
+    score: Res<Score>,
+    mut labels: Query<&mut Text, With<RingsLabel>>
+) {
+    if !score.is_changed()
+    {
+        return;
+    }
+
+    for mut text in labels.iter_mut()
+    {
+        text.sections[0].value = format!("Кольца: {}", score.rings);
+    }
+}
+
+/// Turns the rings held at the goal into points.
+pub fn add_ring_bonus(mut score: ResMut<Score>)
+{
+    score.total += score.rings * RING_BONUS;
+}
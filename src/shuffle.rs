@@ -0,0 +1,148 @@
+use bevy::ecs::system::Res;
+use bevy::ecs::system::ResMut;
+use bevy::ecs::system::Resource;
+use bevy::log::info;
+use bevy::utils::HashMap;
+
+use crate::lives::Lives;
+
+/// Shuffles the answers of every quiz that opens. The order only depends on the seed, the room,
+/// the quiz and how many times it was opened in the run, so a run is replayed with the seed alone.
+#[derive(Resource, Debug)]
+pub struct QuizRng
+{
+    pub seed: u64,
+    /// Times every quiz of the room was opened in this run, by position in `LevelData::quizzes`.
+    attempts: HashMap<usize, u32>
+}
+
+impl QuizRng
+{
+    pub fn new(seed: u64) -> Self
+    {
+        QuizRng { seed, attempts: HashMap::new() }
+    }
+
+    /// Takes the seed from `--seed <number>` on the command line, or picks a new one.
+    pub fn from_args() -> Self
+    {
+        QuizRng::new(seed_arg(std::env::args().skip(1)).unwrap_or_else(|| fastrand::u64(..)))
+    }
+
+    /// Shuffles the answers of quiz `quiz` in room `level` for its next opening.
+    pub fn shuffle<T>(&mut self, level: &str, quiz: usize, items: &mut [T])
+    {
+        let attempt = self.attempts.entry(quiz).or_default();
+        *attempt += 1;
+
+        let mut rng = fastrand::Rng::with_seed(opening_seed(self.seed, level, quiz, *attempt));
+        rng.shuffle(items);
+    }
+}
+
+/// The last `--seed` wins, one that is missing its number or has a bad one gives none.
+fn seed_arg(args: impl IntoIterator<Item = String>) -> Option<u64>
+{
+    let mut args = args.into_iter();
+    let mut seed = None;
+    while let Some(arg) = args.next()
+    {
+        if arg == "--seed"
+        {
+            seed = args.next().and_then(|seed| seed.parse().ok());
+        }
+    }
+
+    seed
+}
+
+/// FNV-1a over everything that tells one opening from another, unlike the std hashers
+/// it stays the same between builds.
+fn opening_seed(seed: u64, level: &str, quiz: usize, attempt: u32) -> u64
+{
+    let bytes = seed
+        .to_le_bytes()
+        .into_iter()
+        .chain(level.bytes())
+        .chain((quiz as u64).to_le_bytes())
+        .chain(attempt.to_le_bytes());
+
+    bytes.fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// Any run can be replayed with the seed from the log.
+pub fn log_quiz_seed(rng: Res<QuizRng>)
+{
+    info!("Quiz seed {0}, replay with --seed {0}", rng.seed);
+}
+
+/// Counts the openings anew for every run, but not when the room is rebuilt after a death.
+pub fn reset_quiz_attempts(mut rng: ResMut<QuizRng>, lives: Res<Lives>)
+{
+    if !lives.respawned
+    {
+        rng.attempts.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String>
+    {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn reads_the_seed_argument()
+    {
+        assert_eq!(seed_arg(args(&["--seed", "42"])), Some(42));
+        assert_eq!(seed_arg(args(&["--windowed", "--seed", "7", "--seed", "9"])), Some(9));
+        assert_eq!(seed_arg(args(&[])), None);
+        assert_eq!(seed_arg(args(&["--seed"])), None);
+        assert_eq!(seed_arg(args(&["--seed", "soon"])), None);
+        assert_eq!(seed_arg(args(&["--seed", "-3"])), None);
+        assert_eq!(seed_arg(args(&["--seed", "18446744073709551616"])), None);
+        assert_eq!(seed_arg(args(&["--seed", "42", "--seed", "x"])), None);
+    }
+
+    #[test]
+    fn opening_seed_depends_on_every_part()
+    {
+        let seed = opening_seed(42, "room_1", 0, 1);
+        assert_eq!(opening_seed(42, "room_1", 0, 1), seed);
+        assert_ne!(opening_seed(43, "room_1", 0, 1), seed);
+        assert_ne!(opening_seed(42, "room_2", 0, 1), seed);
+        assert_ne!(opening_seed(42, "room_1", 1, 1), seed);
+        assert_ne!(opening_seed(42, "room_1", 0, 2), seed);
+    }
+
+    #[test]
+    fn same_seed_shuffles_the_same()
+    {
+        let (mut first, mut second) = (QuizRng::new(42), QuizRng::new(42));
+        for _ in 0..3
+        {
+            let (mut a, mut b): (Vec<u32>, Vec<u32>) = ((0..8).collect(), (0..8).collect());
+            first.shuffle("room_1", 2, &mut a);
+            second.shuffle("room_1", 2, &mut b);
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn openings_are_counted_per_quiz()
+    {
+        let mut rng = QuizRng::new(42);
+        let mut other: Vec<u32> = (0..8).collect();
+        rng.shuffle("room_1", 1, &mut other);
+
+        // Opening another quiz first does not change the order of quiz 2
+        let (mut a, mut b): (Vec<u32>, Vec<u32>) = ((0..8).collect(), (0..8).collect());
+        rng.shuffle("room_1", 2, &mut a);
+        QuizRng::new(42).shuffle("room_1", 2, &mut b);
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,254 @@
+use bevy::asset::Handle;
+use bevy::ecs::component::Component;
+use bevy::ecs::entity::Entity;
+use bevy::ecs::query::With;
+use bevy::ecs::query::Without;
+use bevy::ecs::system::Commands;
+use bevy::ecs::system::Query;
+use bevy::ecs::system::Res;
+use bevy::hierarchy::BuildChildren;
+use bevy::hierarchy::Children;
+use bevy::math::Vec2;
+use bevy::render::color::Color;
+use bevy::render::prelude::SpatialBundle;
+use bevy::render::texture::Image;
+use bevy::sprite::Sprite;
+use bevy::sprite::SpriteBundle;
+use bevy::time::Time;
+use bevy::time::Timer;
+use bevy::time::TimerMode;
+use bevy::transform::components::Transform;
+use bevy_xpbd_2d::components::Collider;
+use bevy_xpbd_2d::components::CollidingEntities;
+use bevy_xpbd_2d::components::CollisionLayers;
+use bevy_xpbd_2d::components::LinearVelocity;
+use bevy_xpbd_2d::components::RigidBody;
+use bevy_xpbd_2d::components::Sensor;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::animations::AnimatableLayer;
+use crate::level::PointData;
+use crate::level::RectData;
+use crate::lives::Dying;
+use crate::player::Immobile;
+use crate::player::Player;
+use crate::player::Layer;
+use crate::system::GameState;
+
+/// Impulse of springs imported from map editors without one, straight up.
+pub const DEFAULT_SPRING_IMPULSE: Vec2 = Vec2::new(0., 180.);
+
+/// Color of springs and bumpers without a texture.
+pub const SPRING_COLOR: Color = Color::rgb(0.9, 0.3, 0.6);
+
+/// Springs are boxes, bumpers are round.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub enum SpringKind
+{
+    #[default]
+    Spring,
+    Bumper
+}
+
+impl SpringKind
+{
+    pub fn parse(name: &str) -> Option<Self>
+    {
+        match name.to_lowercase().as_str()
+        {
+            "spring" | "springs" => Some(SpringKind::Spring),
+            "bumper" | "bumpers" => Some(SpringKind::Bumper),
+            _ => None
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SpringData
+{
+    pub rect: RectData,
+    /// Velocity the player is launched with, in units per second. Zero components keep
+    /// the player's own speed along that axis.
+    pub impulse: PointData,
+    #[serde(default)]
+    pub kind: SpringKind,
+    #[serde(default)]
+    pub texture: String,
+
+    #[serde(skip)]
+    pub image: Handle<Image>
+}
+
+/// How long a spring takes to spring back after launching the player, in seconds.
+const COMPRESS_DURATION: f32 = 0.25;
+
+/// Share of its size a spring is squashed by right after a launch.
+const COMPRESSION: f32 = 0.5;
+
+/// How long after a launch the player can neither jump nor be launched again, in seconds.
+const LAUNCH_DURATION: f32 = 0.3;
+
+/// A spring or bumper, its sprite is a `SpringSprite` child.
+#[derive(Component, Debug)]
+pub struct Spring
+{
+    pub impulse: Vec2,
+    pub kind: SpringKind,
+    pub size: Vec2,
+    /// Runs from the last launch, the spring is at rest once it finishes.
+    pub compress: Timer
+}
+
+impl Spring
+{
+    pub fn new(spring: &SpringData) -> Self
+    {
+        let mut compress = Timer::from_seconds(COMPRESS_DURATION, TimerMode::Once);
+        compress.set_elapsed(compress.duration());
+
+        Spring
+        {
+            impulse: Vec2::new(spring.impulse.x, spring.impulse.y),
+            kind: spring.kind,
+            size: Vec2::new(spring.rect.width, spring.rect.height),
+            compress
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct SpringSprite;
+
+/// The player was just launched. The ground may still be under their feet for a frame
+/// or two, so `update_player_movement` must not turn that into a jump.
+#[derive(Component, Debug)]
+pub struct Launched(pub Timer);
+
+/// Players a spring can launch right now.
+type Launchable = (With<Player>, Without<Launched>, Without<Immobile>, Without<Dying>);
+
+/// Replaces the player's velocity along every axis the spring pushes along.
+pub fn launch_players(
+    mut commands: Commands,
+    mut springs: Query<(&mut Spring, &CollidingEntities)>,
+    mut players: Query<(&mut LinearVelocity, Option<&mut AnimatableLayer>), Launchable>
+) {
+    for (mut spring, colliding_entities) in springs.iter_mut()
+    {
+        for player in colliding_entities.0.iter()
+        {
+            let Ok((mut velocity, animatable)) = players.get_mut(*player) else
+            {
+                continue;
+            };
+
+            if spring.impulse.x != 0.
+            {
+                velocity.x = spring.impulse.x;
+            }
+
+            if spring.impulse.y != 0.
+            {
+                velocity.y = spring.impulse.y;
+            }
+
+            if let Some(mut animatable) = animatable
+            {
+                animatable.next_animation = 4;
+                if spring.impulse.x != 0.
+                {
+                    animatable.flip_x = spring.impulse.x < 0.;
+                }
+            }
+
+            spring.compress.reset();
+            commands.entity(*player).insert(Launched(Timer::from_seconds(LAUNCH_DURATION, TimerMode::Once)));
+        }
+    }
+}
+
+pub fn update_launched(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut players: Query<(Entity, &mut Launched)>
+) {
+    for (player, mut launched) in players.iter_mut()
+    {
+        if launched.0.tick(time.delta()).finished()
+        {
+            commands.entity(player).remove::<Launched>();
+        }
+    }
+}
+
+/// Squashes a spring towards its base, or a bumper towards its center, and lets it spring back.
+pub fn compress_springs(
+    time: Res<Time>,
+    mut springs: Query<(&mut Spring, &Children)>,
+    mut sprites: Query<&mut Transform, With<SpringSprite>>
+) {
+    for (mut spring, children) in springs.iter_mut()
+    {
+        spring.compress.tick(time.delta());
+        let squash = COMPRESSION * (1. - spring.compress.percent());
+
+        let (scale, offset) = match spring.kind
+        {
+            SpringKind::Bumper => (Vec2::splat(1. - squash), Vec2::ZERO),
+            SpringKind::Spring if spring.impulse.x.abs() > spring.impulse.y.abs() =>
+                (Vec2::new(1. - squash, 1.), Vec2::new(-spring.impulse.x.signum() * squash * spring.size.x / 2., 0.)),
+            SpringKind::Spring =>
+                (Vec2::new(1., 1. - squash), Vec2::new(0., -spring.impulse.y.signum() * squash * spring.size.y / 2.))
+        };
+
+        for child in children.iter()
+        {
+            if let Ok(mut transform) = sprites.get_mut(*child)
+            {
+                transform.scale = scale.extend(1.);
+                transform.translation = offset.extend(transform.translation.z);
+            }
+        }
+    }
+}
+
+/// The sprite is a child, so squashing it does not squash the collider.
+pub fn spawn_springs(commands: &mut Commands, springs: &[SpringData])
+{
+    for spring in springs.iter()
+    {
+        let collider = match spring.kind
+        {
+            SpringKind::Spring => Collider::cuboid(spring.rect.width, spring.rect.height),
+            SpringKind::Bumper => Collider::ball(spring.rect.width.min(spring.rect.height) / 2.)
+        };
+
+        commands.spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(spring.rect.x, spring.rect.y, 0.5)),
+            RigidBody::Static,
+            collider,
+            Sensor,
+            CollisionLayers::new([Layer::Interactable], [Layer::Player]),
+            Spring::new(spring),
+            GameState::InGame
+        ))
+        .with_children(|parent|
+        {
+            parent.spawn((
+                SpriteBundle
+                {
+                    texture: spring.image.clone(),
+                    sprite: Sprite
+                    {
+                        color: if spring.texture.is_empty() { SPRING_COLOR } else { Color::WHITE },
+                        custom_size: Some(Vec2::new(spring.rect.width, spring.rect.height)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                SpringSprite
+            ));
+        });
+    }
+}
@@ -0,0 +1,330 @@
+use bevy::asset::Handle;
+use bevy::ecs::change_detection::DetectChanges;
+use bevy::ecs::component::Component;
+use bevy::ecs::query::Changed;
+use bevy::ecs::query::Or;
+use bevy::ecs::query::With;
+use bevy::ecs::query::Without;
+use bevy::ecs::system::Commands;
+use bevy::ecs::system::Query;
+use bevy::ecs::system::Res;
+use bevy::ecs::system::ResMut;
+use bevy::input::keyboard::KeyCode;
+use bevy::math::Vec2;
+use bevy::render::color::Color;
+use bevy::render::texture::Image;
+use bevy::render::view::Visibility;
+use bevy::sprite::Sprite;
+use bevy::sprite::SpriteBundle;
+use bevy::transform::components::Transform;
+use bevy::utils::HashSet;
+use bevy_xpbd_2d::components::Collider;
+use bevy_xpbd_2d::components::CollidingEntities;
+use bevy_xpbd_2d::components::CollisionLayers;
+use bevy_xpbd_2d::components::LockedAxes;
+use bevy_xpbd_2d::components::RigidBody;
+use bevy_xpbd_2d::components::Sensor;
+use leafwing_input_manager::action_state::ActionState;
+use leafwing_input_manager::input_map::InputMap;
+use leafwing_input_manager::InputManagerBundle;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::checkpoint::Checkpoint;
+use crate::interactable::GroundObject;
+use crate::level::RectData;
+use crate::lives::Dying;
+use crate::locks::Unlocks;
+use crate::player::Immobile;
+use crate::player::Layer;
+use crate::player::Player;
+use crate::player::PlayerAction;
+use crate::system::GameState;
+
+/// Color of switched hazards without a texture.
+pub const HAZARD_COLOR: Color = Color::rgb(0.7, 0.1, 0.1);
+
+/// Color of pushable boxes without a texture.
+pub const BOX_COLOR: Color = Color::rgb(0.6, 0.45, 0.3);
+
+/// Levers are flipped with `PlayerAction::Interact`, plates are on while something stands on them.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub enum SwitchKind
+{
+    #[default]
+    Lever,
+    Plate
+}
+
+impl SwitchKind
+{
+    pub fn parse(name: &str) -> Option<Self>
+    {
+        match name.to_lowercase().replace(['_', '-', ' '], "").as_str()
+        {
+            "lever" | "levers" | "switch" | "switches" => Some(SwitchKind::Lever),
+            "plate" | "plates" | "pressureplate" | "pressureplates" => Some(SwitchKind::Plate),
+            _ => None
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SwitchData
+{
+    pub rect: RectData,
+    #[serde(default)]
+    pub kind: SwitchKind,
+    /// Sent while the switch is on. Several switches may send the same signal.
+    pub signal: String,
+    /// Levers that start switched on.
+    #[serde(default)]
+    pub on: bool,
+    #[serde(default)]
+    pub texture: String,
+
+    #[serde(skip)]
+    pub image: Handle<Image>
+}
+
+/// Spikes that a switch retracts, unlike `LevelData::hazards` they are drawn as sprites.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SwitchedHazardData
+{
+    pub rect: RectData,
+    pub signal: String,
+    /// Only out while the signal is sent, instead of only while it is not.
+    #[serde(default)]
+    pub inverted: bool,
+    #[serde(default)]
+    pub texture: String,
+
+    #[serde(skip)]
+    pub image: Handle<Image>
+}
+
+pub const SWITCH_OFF_COLOR: Color = Color::rgb(0.55, 0.55, 0.6);
+pub const SWITCH_ON_COLOR: Color = Color::rgb(0.3, 0.9, 0.3);
+
+/// A lever or pressure plate sending its signal while it is on.
+#[derive(Component, Clone, Debug)]
+pub struct Switch
+{
+    /// Position in `LevelData::switches`, checkpoints remember levers by it.
+    pub index: usize,
+    pub kind: SwitchKind,
+    pub signal: String,
+    pub on: bool
+}
+
+impl Switch
+{
+    /// Levers come back the way they were at the checkpoint, plates always start released.
+    pub fn new(index: usize, switch: &SwitchData, checkpoint: Option<&Checkpoint>) -> Self
+    {
+        let on = match checkpoint
+        {
+            Some(checkpoint) => checkpoint.unlocks.levers.contains(&index),
+            None => switch.on
+        };
+
+        Switch
+        {
+            index,
+            kind: switch.kind,
+            signal: switch.signal.clone(),
+            on: on && switch.kind == SwitchKind::Lever
+        }
+    }
+}
+
+/// A box the player can push onto a pressure plate.
+#[derive(Component, Debug)]
+pub struct Pushable;
+
+/// Spikes that are out while their signal is off, or only while it is on when `inverted`.
+#[derive(Component, Clone, Debug)]
+pub struct SwitchedHazard
+{
+    pub signal: String,
+    pub inverted: bool
+}
+
+/// Players that are free to pull a lever.
+type LeverPullers = (With<Player>, Without<Immobile>, Without<Dying>);
+
+pub fn flip_levers(
+    mut levers: Query<(&ActionState<PlayerAction>, &mut Switch, &CollidingEntities)>,
+    players: Query<(), LeverPullers>
+) {
+    for (action_state, mut switch, colliding_entities) in levers.iter_mut()
+    {
+        if switch.kind == SwitchKind::Lever
+            && action_state.just_pressed(PlayerAction::Interact)
+            && colliding_entities.0.iter().any(|player| players.contains(*player))
+        {
+            switch.on = !switch.on;
+        }
+    }
+}
+
+/// What holds a plate down.
+type Weights = Or<(With<Player>, With<Pushable>)>;
+
+pub fn press_plates(
+    mut plates: Query<(&mut Switch, &CollidingEntities)>,
+    weights: Query<(), Weights>
+) {
+    for (mut switch, colliding_entities) in plates.iter_mut().filter(|(switch, _)| switch.kind == SwitchKind::Plate)
+    {
+        let pressed = colliding_entities.0.iter().any(|entity| weights.contains(*entity));
+        if switch.on != pressed
+        {
+            switch.on = pressed;
+        }
+    }
+}
+
+/// Collects the signals of every switch that is on and of the answered quizzes, `Unlocks` only changes when they do.
+pub fn send_signals(
+    mut unlocks: ResMut<Unlocks>,
+    switches: Query<&Switch>
+) {
+    let signals: HashSet<String> = switches
+        .iter()
+        .filter(|switch| switch.on)
+        .map(|switch| switch.signal.clone())
+        .chain(unlocks.sent.iter().cloned())
+        .collect();
+
+    let levers: HashSet<usize> = switches
+        .iter()
+        .filter(|switch| switch.on && switch.kind == SwitchKind::Lever)
+        .map(|switch| switch.index)
+        .collect();
+
+    if unlocks.signals != signals
+    {
+        unlocks.signals = signals;
+    }
+
+    if unlocks.levers != levers
+    {
+        unlocks.levers = levers;
+    }
+}
+
+pub fn color_switches(mut switches: Query<(&Switch, &mut Sprite), Changed<Switch>>)
+{
+    for (switch, mut sprite) in switches.iter_mut()
+    {
+        sprite.color = if switch.on { SWITCH_ON_COLOR } else { SWITCH_OFF_COLOR };
+    }
+}
+
+pub fn toggle_hazards(
+    unlocks: Res<Unlocks>,
+    mut hazards: Query<(&SwitchedHazard, &mut CollisionLayers, &mut Visibility)>
+) {
+    if !unlocks.is_changed()
+    {
+        return;
+    }
+
+    for (hazard, mut layers, mut visibility) in hazards.iter_mut()
+    {
+        let out = unlocks.signals.contains(&hazard.signal) == hazard.inverted;
+        *layers = if out { CollisionLayers::new([Layer::Enemy], [Layer::Player]) } else { CollisionLayers::none() };
+        *visibility = if out { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
+
+/// Out or retracted is decided by `toggle_hazards` once the signals are known.
+pub fn spawn_switched_hazards(commands: &mut Commands, hazards: &[SwitchedHazardData])
+{
+    for hazard in hazards.iter()
+    {
+        commands.spawn((
+            SpriteBundle
+            {
+                texture: hazard.image.clone(),
+                sprite: Sprite
+                {
+                    color: if hazard.texture.is_empty() { HAZARD_COLOR } else { Color::WHITE },
+                    custom_size: Some(Vec2::new(hazard.rect.width, hazard.rect.height)),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(hazard.rect.x, hazard.rect.y, 0.),
+                ..Default::default()
+            },
+            RigidBody::Static,
+            Collider::cuboid(hazard.rect.width, hazard.rect.height),
+            CollisionLayers::new([Layer::Enemy], [Layer::Player]),
+            GroundObject { next_game_state: GameState::GameOver },
+            SwitchedHazard { signal: hazard.signal.clone(), inverted: hazard.inverted },
+            GameState::InGame
+        ));
+    }
+}
+
+pub fn spawn_switches(commands: &mut Commands, switches: &[SwitchData], checkpoint: Option<&Checkpoint>)
+{
+    for (index, switch) in switches.iter().enumerate()
+    {
+        let entity = commands.spawn((
+            SpriteBundle
+            {
+                texture: switch.image.clone(),
+                sprite: Sprite
+                {
+                    color: SWITCH_OFF_COLOR,
+                    custom_size: Some(Vec2::new(switch.rect.width, switch.rect.height)),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(switch.rect.x, switch.rect.y, 0.5),
+                ..Default::default()
+            },
+            RigidBody::Static,
+            Collider::cuboid(switch.rect.width, switch.rect.height),
+            Sensor,
+            CollisionLayers::new([Layer::Interactable], [Layer::Player, Layer::Ground]),
+            Switch::new(index, switch, checkpoint),
+            GameState::InGame
+        )).id();
+
+        if switch.kind == SwitchKind::Lever
+        {
+            commands.entity(entity).insert(InputManagerBundle::<PlayerAction>
+            {
+                action_state: ActionState::default(),
+                input_map: InputMap::new([ (KeyCode::B, PlayerAction::Interact) ]),
+            });
+        }
+    }
+}
+
+pub fn spawn_boxes(commands: &mut Commands, boxes: &[RectData])
+{
+    for rect in boxes.iter()
+    {
+        commands.spawn((
+            SpriteBundle
+            {
+                sprite: Sprite
+                {
+                    color: BOX_COLOR,
+                    custom_size: Some(Vec2::new(rect.width, rect.height)),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(rect.x, rect.y, 0.),
+                ..Default::default()
+            },
+            RigidBody::Dynamic,
+            Collider::cuboid(rect.width, rect.height),
+            LockedAxes::ROTATION_LOCKED,
+            CollisionLayers::new([Layer::Ground], [Layer::Player, Layer::Ground, Layer::Interactable]),
+            Pushable,
+            GameState::InGame
+        ));
+    }
+}
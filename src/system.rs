@@ -1,4 +1,5 @@
 
+use bevy::asset::Assets;
 use bevy::ecs::component::Component;
 use bevy::ecs::entity::Entity;
 use bevy::ecs::schedule::NextState;
@@ -10,8 +11,14 @@ use bevy::ecs::system::Res;
 use bevy::ecs::system::ResMut;
 use bevy::ecs::system::Resource;
 use bevy::hierarchy::DespawnRecursiveExt;
+use bevy::log::info;
+use serde::Deserialize;
+use serde::Serialize;
 
-#[derive(Debug, Component, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+use crate::assets::GameAssets;
+use crate::campaign::Campaign;
+
+#[derive(Debug, Component, Clone, Copy, Default, Eq, PartialEq, Hash, States, Deserialize, Serialize)]
 pub enum GameState
 {
     #[default]
@@ -21,14 +28,17 @@ pub enum GameState
     InGame,
     GameOver,
     LevelCompleted,
-    FullCompletion
+    FullCompletion,
+    Editor,
+    Respawning
 }
 
 #[derive(Resource, PartialEq)]
-pub struct CurrentLevel(pub u8);
+pub struct CurrentLevel(pub usize);
 
+/// Asks to remove the open quiz popup.
 #[derive(Resource, PartialEq)]
-pub struct QuizClear(pub bool);
+pub struct QuizClear;
 
 pub fn cleanup_after_state(
     mut commands: Commands,
@@ -45,9 +55,30 @@ pub fn cleanup_after_state(
     }
 }
 
+/// The only place the campaign moves on, after the results of a finished room.
 pub fn next_level(
-    game_stats: Res<CurrentLevel>,
+    mut game_stats: ResMut<CurrentLevel>,
+    game_assets: Res<GameAssets>,
+    campaigns: Res<Assets<Campaign>>,
     mut next_game_state: ResMut<NextState<GameState>>,
 ) {
-    next_game_state.set(if game_stats.0 < 3 { GameState::InGame } else { GameState::FullCompletion });
+    let campaign = campaigns.get(&game_assets.campaign);
+    let Some(next) = campaign.and_then(|campaign| campaign.next_level(game_stats.0)) else
+    {
+        next_game_state.set(GameState::FullCompletion);
+        return;
+    };
+
+    game_stats.0 = next;
+    if let Some(world) = campaign.and_then(|campaign| campaign.world_of(next))
+    {
+        info!("Entering room {} ({})", next, world.name);
+    }
+
+    next_game_state.set(GameState::InGame);
+}
+
+pub fn restart_campaign(mut commands: Commands)
+{
+    commands.insert_resource(CurrentLevel(1));
 }
@@ -0,0 +1,744 @@
+use std::path::Path;
+
+use bevy::asset::io::Reader;
+use bevy::asset::AssetLoader;
+use bevy::asset::AsyncReadExt;
+use bevy::asset::LoadContext;
+use bevy::math::Vec2;
+use bevy::utils::BoxedFuture;
+use bevy::utils::HashMap;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::answers::Normalization;
+use crate::enemy::EnemyData;
+use crate::enemy::DEFAULT_ENEMY_SPEED;
+use crate::interactable::QuizAction;
+use crate::interactable::QuizData;
+use crate::interactable::QuizKind;
+use crate::interactable::MAX_ANSWERS;
+use crate::level::LevelData;
+use crate::level::LevelLoaderError;
+use crate::level::PointData;
+use crate::level::resolve_path;
+use crate::level::RectData;
+use crate::level::TileData;
+use crate::level::TileLayerData;
+use crate::level::TilesetData;
+use crate::locks::GateData;
+use crate::locks::KeyData;
+use crate::locks::LockData;
+use crate::platform::PlatformData;
+use crate::platform::PlatformPath;
+use crate::platform::DEFAULT_PLATFORM_SPEED;
+use crate::questions::load_questions;
+use crate::questions::QuestionRef;
+use crate::spring::SpringData;
+use crate::spring::SpringKind;
+use crate::spring::DEFAULT_SPRING_IMPULSE;
+use crate::switches::SwitchData;
+use crate::switches::SwitchKind;
+use crate::switches::SwitchedHazardData;
+use crate::triggers::TriggerAction;
+use crate::triggers::TriggerData;
+use crate::triggers::TriggerEvent;
+
+const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+const FLIPPED_DIAGONALLY: u32 = 0x2000_0000;
+
+/// Imports Tiled maps (`*.tmx` and `*.tmj`) as rooms.
+///
+/// Object layers are matched by the object's class, or by the layer name if the class is empty:
+/// `ground`, `walls`, `gates`, `hazards`, `quizzes`, `checkpoints`, `rings`, `platforms`, `enemies`, `springs`, `bumpers`, `keys`,
+/// `levers`, `plates`, `boxes`, `triggers`, `goal` and `player_start`.
+/// Gates take their sprite from the `texture` property and read a `lock` like `quiz & key:red | key:master`.
+/// Keys are named by the object name and read `texture`. Quizzes read `question`,
+/// `answer_1`..`answer_8`, `correct` (1-based, several separated by commas), `kind` (`single`, `true_false`, `multi_select`, `free_text`,
+/// where every answer is accepted and `normalize` lists the rules kept out of `case`, `whitespace` and `yo`, or `numeric`, where `answer_1` is
+/// the number and its unit, `tolerance` is like `0.1` or `5%` and `units` like `км = 1000; см = 0,01`), `gate` (gate name or object id) and `actions`
+/// (like `open:door; signal:lift; reward:3@96,40`, with gates named as for `gate` and spots in map pixels).
+/// Instead of their own text, quizzes can read `bank` (a `*.questions.ron` file relative to the map) and `pick`
+/// (a question id, or `subject:<subject>` optionally followed by `@<difficulty>`).
+/// Platforms read `path` (the centers of the next stops in map pixels, like `96,40 160,40`),
+/// `speed`, `mode` (`linear`, `ping_pong` or `loop`), `triggered`, `signal` and `texture`. Enemies read
+/// `speed`, `patrol` and a walk cycle from `texture`, `frame_width`, `frame_height` and `frames`.
+/// Springs and bumpers read `impulse_x`, `impulse_y` (in world units, up is positive) and `texture`.
+/// Levers and plates read `signal`, `on` and `texture`. Hazards with a `signal` retract while it is sent,
+/// or only come out then if `inverted`. Triggers read `when` (`enter` for the object's own area, the default,
+/// `correct:<quiz>`, `wrong:<quiz>`, `signal_on:<signal>`, `signal_off:<signal>` or `after:<seconds>`, with quizzes
+/// named by object name or id), `then` (like `open:door; message:Hello; sound:bell.ogg`, relative to the map) and `repeat`.
+/// Tile layers and the first image layer are drawn as the background.
+#[derive(Default)]
+pub struct TiledLoader;
+
+#[derive(Debug, Error)]
+pub enum TiledLoaderError
+{
+    #[error("could not read map file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("could not parse map file: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("could not parse map file: {0}")]
+    Xml(#[from] roxmltree::Error),
+
+    #[error("map file is not valid UTF-8: {0}")]
+    Utf8(#[from] std::str::Utf8Error),
+
+    #[error("invalid map: {0}")]
+    Invalid(String),
+
+    #[error(transparent)]
+    Questions(#[from] LevelLoaderError)
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TiledMap
+{
+    width: u32,
+    height: u32,
+    #[serde(rename = "tilewidth")]
+    tile_width: u32,
+    #[serde(rename = "tileheight")]
+    tile_height: u32,
+    #[serde(default)]
+    tilesets: Vec<TiledTileset>,
+    #[serde(default)]
+    layers: Vec<TiledLayer>
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TiledTileset
+{
+    #[serde(rename = "firstgid")]
+    first_gid: u32,
+    #[serde(default)]
+    source: Option<String>,
+    #[serde(default)]
+    image: String,
+    #[serde(default, rename = "tilewidth")]
+    tile_width: u32,
+    #[serde(default, rename = "tileheight")]
+    tile_height: u32,
+    #[serde(default, rename = "tilecount")]
+    tile_count: u32,
+    #[serde(default)]
+    columns: u32,
+    #[serde(default)]
+    spacing: u32,
+    #[serde(default)]
+    margin: u32
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TiledLayer
+{
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    data: Option<serde_json::Value>,
+    #[serde(default)]
+    objects: Vec<TiledObject>,
+    #[serde(default)]
+    image: String,
+    #[serde(default)]
+    layers: Vec<TiledLayer>
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TiledObject
+{
+    id: u32,
+    #[serde(default)]
+    name: String,
+    #[serde(default, rename = "type")]
+    kind: String,
+    #[serde(default)]
+    class: String,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+    #[serde(default)]
+    properties: Vec<TiledProperty>
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TiledProperty
+{
+    name: String,
+    value: serde_json::Value
+}
+
+impl TiledObject
+{
+    fn property(&self, name: &str) -> Option<String>
+    {
+        self.properties
+            .iter()
+            .find(|property| property.name == name)
+            .map(|property| match &property.value
+            {
+                serde_json::Value::String(value) => value.clone(),
+                value => value.to_string()
+            })
+    }
+
+    fn class(&self) -> &str
+    {
+        if !self.class.is_empty() { &self.class } else { &self.kind }
+    }
+}
+
+impl AssetLoader for TiledLoader
+{
+    type Asset = LevelData;
+    type Settings = ();
+    type Error = TiledLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>>
+    {
+        Box::pin(async move
+        {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+
+            let mut level = parse_map(&bytes, load_context.path())?;
+            load_questions(&mut level, load_context).await?;
+            level.load_dependencies("", load_context);
+            Ok(level)
+        })
+    }
+
+    fn extensions(&self) -> &[&str]
+    {
+        &["tmx", "tmj"]
+    }
+}
+
+/// Reads a map into a room, `path` is the map's location inside the asset folder.
+pub fn parse_map(bytes: &[u8], path: &Path) -> Result<LevelData, TiledLoaderError>
+{
+    let is_xml = path.extension().is_some_and(|extension| extension == "tmx");
+    let map = if is_xml { parse_tmx(std::str::from_utf8(bytes)?)? } else { serde_json::from_slice(bytes)? };
+
+    build_level(&map, path)
+}
+
+fn parse_tmx(text: &str) -> Result<TiledMap, TiledLoaderError>
+{
+    let document = roxmltree::Document::parse(text)?;
+    let root = document.root_element();
+
+    let mut map = TiledMap
+    {
+        width: attribute(root, "width"),
+        height: attribute(root, "height"),
+        tile_width: attribute(root, "tilewidth"),
+        tile_height: attribute(root, "tileheight"),
+        ..Default::default()
+    };
+
+    for node in root.children().filter(|node| node.has_tag_name("tileset"))
+    {
+        let image = node.children().find(|child| child.has_tag_name("image"));
+        map.tilesets.push(TiledTileset
+        {
+            first_gid: attribute(node, "firstgid"),
+            source: node.attribute("source").map(String::from),
+            image: image.and_then(|image| image.attribute("source")).unwrap_or_default().into(),
+            tile_width: attribute(node, "tilewidth"),
+            tile_height: attribute(node, "tileheight"),
+            tile_count: attribute(node, "tilecount"),
+            columns: attribute(node, "columns"),
+            spacing: attribute(node, "spacing"),
+            margin: attribute(node, "margin")
+        });
+    }
+
+    map.layers = parse_tmx_layers(root)?;
+    Ok(map)
+}
+
+fn parse_tmx_layers(parent: roxmltree::Node) -> Result<Vec<TiledLayer>, TiledLoaderError>
+{
+    let mut layers = Vec::new();
+    for node in parent.children().filter(|node| node.is_element())
+    {
+        let name = node.attribute("name").unwrap_or_default().to_string();
+        match node.tag_name().name()
+        {
+            "layer" =>
+            {
+                let data = node.children().find(|child| child.has_tag_name("data"));
+                let encoding = data.and_then(|data| data.attribute("encoding"));
+                if encoding != Some("csv")
+                {
+                    return Err(TiledLoaderError::Invalid(format!("layer `{name}` must use CSV encoding")));
+                }
+
+                let gids: Vec<u32> = data
+                    .and_then(|data| data.text())
+                    .unwrap_or_default()
+                    .split(',')
+                    .filter_map(|gid| gid.trim().parse::<u32>().ok())
+                    .collect();
+
+                layers.push(TiledLayer { kind: "tilelayer".into(), name, data: Some(serde_json::Value::from(gids)), ..Default::default() });
+            },
+            "objectgroup" =>
+            {
+                let objects = node.children()
+                    .filter(|child| child.has_tag_name("object"))
+                    .map(parse_tmx_object)
+                    .collect();
+
+                layers.push(TiledLayer { kind: "objectgroup".into(), name, objects, ..Default::default() });
+            },
+            "imagelayer" =>
+            {
+                let image = node.children()
+                    .find(|child| child.has_tag_name("image"))
+                    .and_then(|image| image.attribute("source"))
+                    .unwrap_or_default()
+                    .to_string();
+
+                layers.push(TiledLayer { kind: "imagelayer".into(), name, image, ..Default::default() });
+            },
+            "group" =>
+            {
+                layers.push(TiledLayer { kind: "group".into(), name, layers: parse_tmx_layers(node)?, ..Default::default() });
+            },
+            _ => {}
+        }
+    }
+
+    Ok(layers)
+}
+
+fn parse_tmx_object(node: roxmltree::Node) -> TiledObject
+{
+    let properties = node.children()
+        .filter(|child| child.has_tag_name("properties"))
+        .flat_map(|properties| properties.children().filter(|child| child.has_tag_name("property")))
+        .map(|property| TiledProperty
+        {
+            name: property.attribute("name").unwrap_or_default().into(),
+            value: property.attribute("value").or(property.text()).unwrap_or_default().into()
+        })
+        .collect();
+
+    TiledObject
+    {
+        id: attribute(node, "id"),
+        name: node.attribute("name").unwrap_or_default().into(),
+        kind: node.attribute("type").unwrap_or_default().into(),
+        class: node.attribute("class").unwrap_or_default().into(),
+        x: attribute(node, "x"),
+        y: attribute(node, "y"),
+        width: attribute(node, "width"),
+        height: attribute(node, "height"),
+        properties
+    }
+}
+
+fn attribute<T: std::str::FromStr + Default>(node: roxmltree::Node, name: &str) -> T
+{
+    node.attribute(name).and_then(|value| value.parse().ok()).unwrap_or_default()
+}
+
+fn build_level(map: &TiledMap, path: &Path) -> Result<LevelData, TiledLoaderError>
+{
+    let map_size = Vec2::new((map.width * map.tile_width) as f32, (map.height * map.tile_height) as f32);
+    let to_world = |x: f32, y: f32| Vec2::new(x - map_size.x / 2., map_size.y / 2. - y);
+    let to_rect = |object: &TiledObject|
+    {
+        let center = to_world(object.x + object.width / 2., object.y + object.height / 2.);
+        RectData { x: center.x, y: center.y, width: object.width, height: object.height }
+    };
+
+    let mut level = LevelData { bounds: Some(RectData::centered(Vec2::ZERO, map_size)), ..Default::default() };
+    let mut tilesets = Vec::new();
+    for tileset in map.tilesets.iter()
+    {
+        if let Some(source) = &tileset.source
+        {
+            return Err(TiledLoaderError::Invalid(format!("external tileset `{source}` is not supported, embed it into the map")));
+        }
+
+        let columns = tileset.columns.max(1);
+        tilesets.push((tileset.first_gid, TilesetData
+        {
+            image: resolve_path(path, &tileset.image),
+            tile_size: Vec2::new(tileset.tile_width as f32, tileset.tile_height as f32),
+            columns: columns as usize,
+            rows: tileset.tile_count.div_ceil(columns) as usize,
+            spacing: tileset.spacing as f32,
+            margin: tileset.margin as f32
+        }));
+    }
+
+    let mut layers = Vec::new();
+    flatten_layers(&map.layers, &mut layers);
+
+    let mut gate_ids = HashMap::new();
+    let mut quizzes = Vec::new();
+    let mut triggers = Vec::new();
+    for (depth, layer) in layers.iter().enumerate()
+    {
+        match layer.kind.as_str()
+        {
+            "tilelayer" =>
+            {
+                let gids: Vec<u32> = match &layer.data
+                {
+                    Some(serde_json::Value::Array(gids)) => gids.iter().filter_map(|gid| gid.as_u64()).map(|gid| gid as u32).collect(),
+                    _ => return Err(TiledLoaderError::Invalid(format!("layer `{}` must use CSV encoding", layer.name)))
+                };
+
+                let mut by_tileset: HashMap<usize, TileLayerData> = HashMap::new();
+                for (cell, gid) in gids.iter().enumerate()
+                {
+                    let id = gid & !(FLIPPED_HORIZONTALLY | FLIPPED_VERTICALLY | FLIPPED_DIAGONALLY);
+                    if id == 0
+                    {
+                        continue;
+                    }
+
+                    let Some(tileset_index) = tilesets.iter().rposition(|(first_gid, _)| *first_gid <= id) else
+                    {
+                        continue;
+                    };
+
+                    let column = cell as u32 % map.width;
+                    let row = cell as u32 / map.width;
+                    let position = to_world(
+                        ((column * map.tile_width) as f32) + map.tile_width as f32 / 2.,
+                        ((row * map.tile_height) as f32) + map.tile_height as f32 / 2.
+                    );
+
+                    let (first_gid, tileset) = &tilesets[tileset_index];
+                    by_tileset.entry(tileset_index)
+                        .or_insert_with(|| TileLayerData { tileset: tileset.clone(), z: -1. + depth as f32 * 0.01, ..Default::default() })
+                        .tiles
+                        .push(TileData
+                        {
+                            index: (id - first_gid) as usize,
+                            x: position.x,
+                            y: position.y,
+                            flip_x: gid & FLIPPED_HORIZONTALLY != 0,
+                            flip_y: gid & FLIPPED_VERTICALLY != 0
+                        });
+                }
+
+                level.tile_layers.extend(by_tileset.into_values());
+            },
+            "imagelayer" if level.background.is_empty() && !layer.image.is_empty() =>
+            {
+                level.background = resolve_path(path, &layer.image);
+            },
+            "objectgroup" =>
+            {
+                for object in layer.objects.iter()
+                {
+                    let class = if object.class().is_empty() { layer.name.as_str() } else { object.class() };
+                    match class.to_lowercase().as_str()
+                    {
+                        "ground" => level.ground.push(to_rect(object)),
+                        "wall" | "walls" => level.walls.push(to_rect(object)),
+                        "hazard" | "hazards" => match object.property("signal")
+                        {
+                            Some(signal) => level.switched_hazards.push(SwitchedHazardData
+                            {
+                                rect: to_rect(object),
+                                signal,
+                                inverted: object.property("inverted").is_some_and(|inverted| inverted == "true"),
+                                texture: object.property("texture").map(|texture| resolve_path(path, &texture)).unwrap_or_default(),
+                                ..Default::default()
+                            }),
+                            None => level.hazards.push(to_rect(object))
+                        },
+                        "box" | "boxes" | "crate" | "crates" => level.boxes.push(to_rect(object)),
+                        "goal" => level.goal = to_rect(object),
+                        "checkpoint" | "checkpoints" | "star_post" =>
+                        {
+                            let rect = to_rect(object);
+                            level.checkpoints.push(PointData { x: rect.x, y: rect.y });
+                        },
+                        "ring" | "rings" =>
+                        {
+                            let rect = to_rect(object);
+                            level.rings.push(PointData { x: rect.x, y: rect.y });
+                        },
+                        "player_start" | "spawn" =>
+                        {
+                            let position = to_world(object.x, object.y);
+                            level.player_start = PointData { x: position.x, y: position.y };
+                        },
+                        "gate" | "gates" =>
+                        {
+                            let id = if object.name.is_empty() { object.id.to_string() } else { object.name.clone() };
+                            gate_ids.insert(object.id.to_string(), id.clone());
+                            gate_ids.insert(object.name.clone(), id.clone());
+
+                            let lock = match object.property("lock")
+                            {
+                                Some(lock) => LockData::parse(&lock)
+                                    .ok_or_else(|| TiledLoaderError::Invalid(format!("gate `{id}` has an unknown lock `{lock}`")))?,
+                                None => LockData::Quiz
+                            };
+
+                            level.gates.push(GateData
+                            {
+                                id,
+                                texture: object.property("texture").map(|texture| resolve_path(path, &texture)).unwrap_or_default(),
+                                rect: to_rect(object),
+                                lock,
+                                ..Default::default()
+                            });
+                        },
+                        "platform" | "platforms" =>
+                        {
+                            let path_stops = object.property("path")
+                                .unwrap_or_default()
+                                .split_whitespace()
+                                .filter_map(|stop| stop.split_once(','))
+                                .filter_map(|(x, y)| Some(to_world(x.parse().ok()?, y.parse().ok()?)))
+                                .map(|stop| PointData { x: stop.x, y: stop.y })
+                                .collect();
+
+                            level.platforms.push(PlatformData
+                            {
+                                rect: to_rect(object),
+                                path: path_stops,
+                                speed: object.property("speed").and_then(|speed| speed.parse().ok()).unwrap_or(DEFAULT_PLATFORM_SPEED),
+                                mode: object.property("mode").and_then(|mode| PlatformPath::parse(&mode)).unwrap_or_default(),
+                                triggered: object.property("triggered").is_some_and(|triggered| triggered == "true"),
+                                signal: object.property("signal").unwrap_or_default(),
+                                texture: object.property("texture").map(|texture| resolve_path(path, &texture)).unwrap_or_default(),
+                                ..Default::default()
+                            });
+                        },
+                        "enemy" | "enemies" =>
+                        {
+                            let number = |name: &str| object.property(name).and_then(|value| value.parse::<f32>().ok());
+                            level.enemies.push(EnemyData
+                            {
+                                rect: to_rect(object),
+                                speed: number("speed").unwrap_or(DEFAULT_ENEMY_SPEED),
+                                patrol: number("patrol").unwrap_or_default(),
+                                texture: object.property("texture").map(|texture| resolve_path(path, &texture)).unwrap_or_default(),
+                                frame_width: number("frame_width").unwrap_or(object.width),
+                                frame_height: number("frame_height").unwrap_or(object.height),
+                                frames: number("frames").map_or(1, |frames| frames as usize),
+                                ..Default::default()
+                            });
+                        },
+                        "key" | "keys" =>
+                        {
+                            let rect = to_rect(object);
+                            level.keys.push(KeyData
+                            {
+                                id: if object.name.is_empty() { object.id.to_string() } else { object.name.clone() },
+                                x: rect.x,
+                                y: rect.y,
+                                texture: object.property("texture").map(|texture| resolve_path(path, &texture)).unwrap_or_default(),
+                                ..Default::default()
+                            });
+                        },
+                        "spring" | "springs" | "bumper" | "bumpers" =>
+                        {
+                            let number = |name: &str| object.property(name).and_then(|value| value.parse::<f32>().ok());
+                            let impulse = match (number("impulse_x"), number("impulse_y"))
+                            {
+                                (None, None) => DEFAULT_SPRING_IMPULSE,
+                                (x, y) => Vec2::new(x.unwrap_or_default(), y.unwrap_or_default())
+                            };
+
+                            level.springs.push(SpringData
+                            {
+                                rect: to_rect(object),
+                                impulse: PointData { x: impulse.x, y: impulse.y },
+                                kind: SpringKind::parse(class).unwrap_or_default(),
+                                texture: object.property("texture").map(|texture| resolve_path(path, &texture)).unwrap_or_default(),
+                                ..Default::default()
+                            });
+                        },
+                        "lever" | "levers" | "switch" | "switches" | "plate" | "plates" | "pressure_plate" | "pressure_plates" =>
+                        {
+                            level.switches.push(SwitchData
+                            {
+                                rect: to_rect(object),
+                                kind: SwitchKind::parse(class).unwrap_or_default(),
+                                signal: object.property("signal").unwrap_or_default(),
+                                on: object.property("on").is_some_and(|on| on == "true"),
+                                texture: object.property("texture").map(|texture| resolve_path(path, &texture)).unwrap_or_default(),
+                                ..Default::default()
+                            });
+                        },
+                        "quiz" | "quizzes" => quizzes.push(object),
+                        "trigger" | "triggers" => triggers.push(object),
+                        _ => {}
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+
+    let mut quiz_ids = HashMap::new();
+    for object in quizzes
+    {
+        let id = if object.name.is_empty() { object.id.to_string() } else { object.name.clone() };
+        quiz_ids.insert(object.id.to_string(), id.clone());
+        quiz_ids.insert(object.name.clone(), id.clone());
+
+        let correct: Vec<usize> = object.property("correct")
+            .unwrap_or_else(|| String::from("1"))
+            .split(',')
+            .filter_map(|correct| correct.trim().parse().ok())
+            .collect();
+        let gate = object.property("gate").and_then(|gate| gate_ids.get(&gate).cloned());
+
+        let mut answers: Vec<String> = (1..=MAX_ANSWERS)
+            .map(|index| object.property(&format!("answer_{index}")).unwrap_or_default())
+            .collect();
+        while answers.last().is_some_and(String::is_empty)
+        {
+            answers.pop();
+        }
+
+        let kind = object.property("kind").unwrap_or_default();
+        let kind = match kind.as_str()
+        {
+            "" => QuizKind::default(),
+            kind => QuizKind::parse(kind).ok_or_else(|| TiledLoaderError::Invalid(format!("quiz {} has unknown kind `{kind}`", object.id)))?
+        };
+
+        let actions = match object.property("actions")
+        {
+            Some(actions) => QuizAction::parse_list(&actions)
+                .ok_or_else(|| TiledLoaderError::Invalid(format!("quiz {} has unknown actions `{actions}`", object.id)))?,
+            None => Vec::new()
+        };
+
+        let mut quiz = QuizData::from_answers(
+            to_rect(object),
+            gate,
+            kind,
+            object.property("question").unwrap_or_default(),
+            &answers,
+            &correct
+        );
+        quiz.id = id;
+
+        if let Some(normalize) = object.property("normalize")
+        {
+            quiz.normalize = Normalization::parse(&normalize)
+                .ok_or_else(|| TiledLoaderError::Invalid(format!("quiz {} has unknown normalization `{normalize}`", object.id)))?;
+        }
+
+        if let Some(numeric) = quiz.numeric.as_mut()
+        {
+            if let Some(tolerance) = object.property("tolerance")
+            {
+                numeric.parse_tolerance(&tolerance)
+                    .ok_or_else(|| TiledLoaderError::Invalid(format!("quiz {} has unknown tolerance `{tolerance}`", object.id)))?;
+            }
+
+            if let Some(units) = object.property("units")
+            {
+                numeric.parse_units(&units)
+                    .ok_or_else(|| TiledLoaderError::Invalid(format!("quiz {} has unknown units `{units}`", object.id)))?;
+            }
+        }
+        else if kind == QuizKind::Numeric && object.property("bank").is_none()
+        {
+            return Err(TiledLoaderError::Invalid(format!("quiz {} has no number for its answer", object.id)));
+        }
+
+        if let Some(bank) = object.property("bank")
+        {
+            let pick = object.property("pick").unwrap_or_default();
+            quiz.from_bank = Some(QuestionRef::parse(resolve_path(path, &bank), &pick)
+                .ok_or_else(|| TiledLoaderError::Invalid(format!("quiz {} has unknown pick `{pick}`", object.id)))?);
+        }
+
+        // Gates may be named by object id, reward spots are in map pixels
+        quiz.actions = actions
+            .into_iter()
+            .map(|action| match action
+            {
+                QuizAction::Unlock(id) => QuizAction::Unlock(gate_ids.get(&id).cloned().unwrap_or(id)),
+                QuizAction::Open(id) => QuizAction::Open(gate_ids.get(&id).cloned().unwrap_or(id)),
+                QuizAction::Reward { x, y, rings } =>
+                {
+                    let spot = to_world(x, y);
+                    QuizAction::Reward { x: spot.x, y: spot.y, rings }
+                },
+                action => action
+            })
+            .collect();
+
+        level.quizzes.push(quiz);
+    }
+
+    for object in triggers
+    {
+        let when = object.property("when").unwrap_or_else(|| String::from("enter"));
+        let when = TriggerEvent::parse(&when, to_rect(object), |quiz| quiz_ids.get(quiz).cloned())
+            .ok_or_else(|| TiledLoaderError::Invalid(format!("trigger {} has unknown event `{when}`", object.id)))?;
+
+        let then = object.property("then").unwrap_or_default();
+        let then = TriggerAction::parse_list(&then)
+            .ok_or_else(|| TiledLoaderError::Invalid(format!("trigger {} has unknown actions `{then}`", object.id)))?;
+
+        level.triggers.push(TriggerData
+        {
+            when,
+            then: then
+                .into_iter()
+                .map(|action| match action
+                {
+                    TriggerAction::OpenGate(id) => TriggerAction::OpenGate(gate_ids.get(&id).cloned().unwrap_or(id)),
+                    TriggerAction::Reward { x, y, rings } =>
+                    {
+                        let spot = to_world(x, y);
+                        TriggerAction::Reward { x: spot.x, y: spot.y, rings }
+                    },
+                    TriggerAction::Sound(sound) => TriggerAction::Sound(resolve_path(path, &sound)),
+                    action => action
+                })
+                .collect(),
+            repeat: object.property("repeat").is_some_and(|repeat| repeat == "true")
+        });
+    }
+
+    Ok(level)
+}
+
+fn flatten_layers<'a>(layers: &'a [TiledLayer], flattened: &mut Vec<&'a TiledLayer>)
+{
+    for layer in layers
+    {
+        if layer.kind == "group"
+        {
+            flatten_layers(&layer.layers, flattened);
+        }
+        else
+        {
+            flattened.push(layer);
+        }
+    }
+}
@@ -0,0 +1,398 @@
+use bevy::asset::AssetServer;
+use bevy::audio::AudioBundle;
+use bevy::asset::Handle;
+use bevy::audio::PlaybackSettings;
+use bevy::ecs::component::Component;
+use bevy::ecs::entity::Entity;
+use bevy::ecs::event::Event;
+use bevy::ecs::event::EventReader;
+use bevy::ecs::query::With;
+use bevy::ecs::query::Without;
+use bevy::ecs::schedule::NextState;
+use bevy::ecs::system::Commands;
+use bevy::ecs::system::Query;
+use bevy::ecs::system::Res;
+use bevy::ecs::system::ResMut;
+use bevy::ecs::system::Resource;
+use bevy::ecs::system::SystemParam;
+use bevy::hierarchy::DespawnRecursiveExt;
+use bevy::math::Vec2;
+use bevy::render::color::Color;
+use bevy::text::Font;
+use bevy::text::TextAlignment;
+use bevy::text::TextStyle;
+use bevy::time::Time;
+use bevy::time::Timer;
+use bevy::time::TimerMode;
+use bevy::transform::components::Transform;
+use bevy::ui::node_bundles::TextBundle;
+use bevy::ui::JustifySelf;
+use bevy::ui::PositionType;
+use bevy::ui::Style;
+use bevy::ui::Val;
+use bevy::utils::HashSet;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::assets::GameAssets;
+use crate::enemy::spawn_enemy;
+use crate::enemy::EnemyData;
+use crate::interactable::QuizAction;
+use crate::level::CurrentLevelData;
+use crate::level::RectData;
+use crate::lives::Dying;
+use crate::locks::Gate;
+use crate::locks::fade_gate;
+use crate::locks::Fading;
+use crate::locks::Unlocks;
+use crate::player::Player;
+use crate::player::PLAYER_SIZE;
+use crate::rings::spawn_ring_row;
+use crate::system::GameState;
+
+/// How long trigger messages imported from map editors stay on screen, in seconds.
+pub const DEFAULT_MESSAGE_SECONDS: f32 = 3.;
+
+/// What sets a trigger off.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub enum TriggerEvent
+{
+    /// The player steps into the area.
+    Enter(RectData),
+    /// The quiz with this id is answered, rightly or wrongly.
+    Answered { quiz: String, correct: bool },
+    /// The signal starts or stops being sent.
+    Signal { signal: String, on: bool },
+    /// Seconds since the room started, a repeating trigger goes off every time they pass again.
+    Elapsed(f32)
+}
+
+impl TriggerEvent
+{
+    /// One of `enter` (the trigger's own area), `correct:<quiz>`, `wrong:<quiz>`, `signal_on:<signal>`,
+    /// `signal_off:<signal>` or `after:<seconds>`, with zero or more seconds.
+    /// `quiz` looks up the id of a quiz by whatever the map editor names it with.
+    pub fn parse(text: &str, area: RectData, quiz: impl Fn(&str) -> Option<String>) -> Option<Self>
+    {
+        match text.split_once(':').map(|(kind, target)| (kind.trim(), target.trim()))
+        {
+            None if text.trim() == "enter" => Some(TriggerEvent::Enter(area)),
+            Some(("correct", target)) => Some(TriggerEvent::Answered { quiz: quiz(target)?, correct: true }),
+            Some(("wrong", target)) => Some(TriggerEvent::Answered { quiz: quiz(target)?, correct: false }),
+            Some(("signal_on", signal)) if !signal.is_empty() => Some(TriggerEvent::Signal { signal: signal.to_string(), on: true }),
+            Some(("signal_off", signal)) if !signal.is_empty() => Some(TriggerEvent::Signal { signal: signal.to_string(), on: false }),
+            Some(("after", seconds)) => seconds.parse().ok().filter(|seconds: &f32| seconds.is_finite() && *seconds >= 0.).map(TriggerEvent::Elapsed),
+            _ => None
+        }
+    }
+}
+
+/// Something a trigger does to the room.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum TriggerAction
+{
+    /// Opens the gate whatever its lock says, it fades away.
+    OpenGate(String),
+    /// Sends the signal for the rest of the room.
+    Signal(String),
+    /// Rings laid out in a row centered on the spot.
+    Reward { x: f32, y: f32, rings: u32 },
+    SpawnEnemy(EnemyData),
+    /// Text shown at the top of the screen for a few seconds.
+    Message { text: String, seconds: f32 },
+    /// Switching to `LevelCompleted` counts as reaching the goal.
+    State(GameState),
+    /// Path of a sound inside the asset folder.
+    Sound(String)
+}
+
+impl TriggerAction
+{
+    /// The `then` of a trigger, like
+    /// `open:door; signal:lift; reward:3@96,40; message:Well done!; state:level_completed; sound:sfx/bell.ogg`.
+    /// Reward spots stay in the map's own units, enemies can only be spawned from `*.level.ron` files.
+    pub fn parse_list(text: &str) -> Option<Vec<Self>>
+    {
+        text
+            .split(';')
+            .map(str::trim)
+            .filter(|action| !action.is_empty())
+            .map(|action| match action.split_once(':').map(|(kind, target)| (kind.trim(), target.trim()))
+            {
+                Some((_, "")) => None,
+                Some(("open", id)) => Some(TriggerAction::OpenGate(id.to_string())),
+                Some(("signal", id)) => Some(TriggerAction::Signal(id.to_string())),
+                Some(("reward", reward)) => match QuizAction::parse_list(&format!("reward:{reward}"))?.pop()?
+                {
+                    QuizAction::Reward { x, y, rings } => Some(TriggerAction::Reward { x, y, rings }),
+                    _ => None
+                },
+                Some(("message", text)) => Some(TriggerAction::Message { text: text.to_string(), seconds: DEFAULT_MESSAGE_SECONDS }),
+                Some(("state", state)) => match state
+                {
+                    "main_menu" => Some(TriggerAction::State(GameState::MainMenu)),
+                    "game_over" => Some(TriggerAction::State(GameState::GameOver)),
+                    "level_completed" => Some(TriggerAction::State(GameState::LevelCompleted)),
+                    _ => None
+                },
+                Some(("sound", path)) => Some(TriggerAction::Sound(path.to_string())),
+                _ => None
+            })
+            .collect()
+    }
+}
+
+/// When `when` happens, do everything in `then`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TriggerData
+{
+    pub when: TriggerEvent,
+    pub then: Vec<TriggerAction>,
+    /// Goes off every time instead of only the first time.
+    #[serde(default)]
+    pub repeat: bool
+}
+
+/// Sent whenever a quiz is answered, triggers listen for it.
+#[derive(Event, Clone, Debug)]
+pub struct QuizAnswered
+{
+    /// The quiz's id, empty if triggers can not name it.
+    pub quiz: String,
+    /// Position of the quiz in `LevelData::quizzes`.
+    pub index: usize,
+    pub correct: bool
+}
+
+/// The triggers of the current room and what they saw last frame.
+#[derive(Resource, Debug, Default)]
+pub struct Triggers
+{
+    pub triggers: Vec<TriggerData>,
+    pub fired: Vec<bool>,
+    /// Whether the player stood in the area of each `Enter` trigger.
+    pub inside: Vec<bool>,
+    pub signals: HashSet<String>,
+    /// Seconds since the room started.
+    pub elapsed: f32
+}
+
+#[derive(Component, Debug)]
+pub struct TriggerMessage(pub Timer);
+
+/// Everything trigger actions may change.
+#[derive(SystemParam)]
+pub struct TriggerTargets<'w, 's>
+{
+    commands: Commands<'w, 's>,
+    game_assets: Res<'w, GameAssets>,
+    asset_server: Res<'w, AssetServer>,
+    unlocks: ResMut<'w, Unlocks>,
+    next_game_state: ResMut<'w, NextState<GameState>>,
+    gates: Query<'w, 's, (Entity, &'static Gate), Without<Fading>>
+}
+
+impl<'w, 's> TriggerTargets<'w, 's>
+{
+    fn apply(&mut self, action: &TriggerAction)
+    {
+        match action
+        {
+            TriggerAction::OpenGate(id) =>
+            {
+                for (entity, _) in self.gates.iter().filter(|(_, gate)| gate.0 == *id)
+                {
+                    fade_gate(&mut self.commands, entity);
+                }
+            },
+            TriggerAction::Signal(id) => { self.unlocks.sent.insert(id.clone()); },
+            TriggerAction::Reward { x, y, rings } => spawn_ring_row(&mut self.commands, Vec2::new(*x, *y), *rings),
+            TriggerAction::SpawnEnemy(enemy) => { spawn_enemy(&mut self.commands, enemy); },
+            TriggerAction::Message { text, seconds } =>
+                spawn_message(&mut self.commands, self.game_assets.main_font.clone(), text.clone(), *seconds),
+            TriggerAction::State(state) => self.next_game_state.set(*state),
+            TriggerAction::Sound(path) =>
+            {
+                self.commands.spawn((
+                    AudioBundle
+                    {
+                        source: self.asset_server.load(path.clone()),
+                        settings: PlaybackSettings::DESPAWN
+                    },
+                    GameState::InGame
+                ));
+            }
+        }
+    }
+}
+
+/// Text at the top of the screen that goes away after `seconds`.
+pub fn spawn_message(commands: &mut Commands, font: Handle<Font>, text: String, seconds: f32)
+{
+    commands.spawn((
+        TextBundle::from_section(
+            text,
+            TextStyle
+            {
+                font,
+                font_size: 30.0,
+                color: Color::WHITE
+            }
+        )
+        .with_text_alignment(TextAlignment::Center)
+        .with_style(Style
+        {
+            position_type: PositionType::Absolute,
+            justify_self: JustifySelf::Center,
+            top: Val::Px(120.0),
+            ..Default::default()
+        }),
+        TriggerMessage(Timer::from_seconds(seconds, TimerMode::Once)),
+        GameState::InGame
+    ));
+}
+
+/// Every attempt at the room starts with no trigger set off.
+pub fn reset_triggers(mut commands: Commands, current_level: CurrentLevelData)
+{
+    let triggers = current_level.get().map(|level| level.triggers.clone()).unwrap_or_default();
+    commands.insert_resource(Triggers
+    {
+        fired: vec![false; triggers.len()],
+        inside: vec![false; triggers.len()],
+        triggers,
+        ..Default::default()
+    });
+}
+
+pub fn run_triggers(
+    time: Res<Time>,
+    mut triggers: ResMut<Triggers>,
+    mut answers: EventReader<QuizAnswered>,
+    players: Query<&Transform, (With<Player>, Without<Dying>)>,
+    mut targets: TriggerTargets
+) {
+    let answers: Vec<QuizAnswered> = answers.read().cloned().collect();
+    let bodies: Vec<RectData> = players.iter().map(|transform| RectData::centered(transform.translation.truncate(), PLAYER_SIZE)).collect();
+
+    let delta = time.delta_seconds();
+    triggers.elapsed += delta;
+
+    let Triggers { triggers, fired, inside, signals, elapsed } = &mut *triggers;
+    for (index, trigger) in triggers.iter().enumerate()
+    {
+        let set_off = match &trigger.when
+        {
+            TriggerEvent::Enter(area) =>
+            {
+                let now = bodies.iter().any(|body| body.overlaps(area));
+                let entered = now && !inside[index];
+                inside[index] = now;
+                entered
+            },
+            TriggerEvent::Answered { quiz, correct } =>
+                !quiz.is_empty() && answers.iter().any(|answer| answer.quiz == *quiz && answer.correct == *correct),
+            TriggerEvent::Signal { signal, on } =>
+                targets.unlocks.signals.contains(signal) == *on && signals.contains(signal) != *on,
+            TriggerEvent::Elapsed(seconds) if *seconds <= 0. => !fired[index],
+            TriggerEvent::Elapsed(seconds) => (*elapsed / seconds).floor() > ((*elapsed - delta) / seconds).floor()
+        };
+
+        if set_off && (trigger.repeat || !fired[index])
+        {
+            fired[index] = true;
+            for action in trigger.then.iter()
+            {
+                targets.apply(action);
+            }
+        }
+    }
+
+    *signals = targets.unlocks.signals.clone();
+}
+
+pub fn expire_messages(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut messages: Query<(Entity, &mut TriggerMessage)>
+) {
+    for (entity, mut message) in messages.iter_mut()
+    {
+        if message.0.tick(time.delta()).finished()
+        {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::level::RectData;
+
+    fn quiz_named(name: &str) -> Option<String>
+    {
+        match name
+        {
+            "first" | "1" => Some(String::from("first")),
+            "second" | "2" => Some(String::from("second")),
+            _ => None
+        }
+    }
+
+    #[test]
+    fn parses_trigger_events()
+    {
+        let area = RectData { x: 1., y: 2., width: 3., height: 4. };
+        assert_eq!(TriggerEvent::parse("enter", area, quiz_named), Some(TriggerEvent::Enter(area)));
+        assert_eq!(TriggerEvent::parse("correct:second", area, quiz_named), Some(TriggerEvent::Answered { quiz: String::from("second"), correct: true }));
+        assert_eq!(TriggerEvent::parse("wrong: first", area, quiz_named), Some(TriggerEvent::Answered { quiz: String::from("first"), correct: false }));
+        assert_eq!(TriggerEvent::parse("correct:2", area, quiz_named), Some(TriggerEvent::Answered { quiz: String::from("second"), correct: true }));
+        assert_eq!(
+            TriggerEvent::parse("signal_off:lift", area, quiz_named),
+            Some(TriggerEvent::Signal { signal: String::from("lift"), on: false })
+        );
+        assert_eq!(TriggerEvent::parse("after:2.5", area, quiz_named), Some(TriggerEvent::Elapsed(2.5)));
+    }
+
+    #[test]
+    fn rejects_malformed_trigger_events()
+    {
+        let area = RectData::default();
+        assert_eq!(TriggerEvent::parse("correct:third", area, quiz_named), None);
+        assert_eq!(TriggerEvent::parse("signal_on:", area, quiz_named), None);
+        assert_eq!(TriggerEvent::parse("after:soon", area, quiz_named), None);
+        assert_eq!(TriggerEvent::parse("after:-1", area, quiz_named), None);
+        assert_eq!(TriggerEvent::parse("after:inf", area, quiz_named), None);
+        assert_eq!(TriggerEvent::parse("after:NaN", area, quiz_named), None);
+        assert_eq!(TriggerEvent::parse("leave", area, quiz_named), None);
+        assert_eq!(TriggerEvent::parse("", area, quiz_named), None);
+    }
+
+    #[test]
+    fn parses_trigger_actions()
+    {
+        let actions = TriggerAction::parse_list(
+            "open:door; signal:lift; reward:2@10,20; message:Well done!; state:level_completed; sound:sfx/bell.ogg"
+        ).unwrap();
+
+        assert_eq!(actions.len(), 6);
+        assert!(matches!(&actions[0], TriggerAction::OpenGate(id) if id == "door"));
+        assert!(matches!(&actions[1], TriggerAction::Signal(id) if id == "lift"));
+        assert!(matches!(actions[2], TriggerAction::Reward { x, y, rings: 2 } if x == 10. && y == 20.));
+        assert!(matches!(&actions[3], TriggerAction::Message { text, seconds } if text == "Well done!" && *seconds == DEFAULT_MESSAGE_SECONDS));
+        assert!(matches!(actions[4], TriggerAction::State(GameState::LevelCompleted)));
+        assert!(matches!(&actions[5], TriggerAction::Sound(path) if path == "sfx/bell.ogg"));
+    }
+
+    #[test]
+    fn rejects_malformed_trigger_actions()
+    {
+        assert!(TriggerAction::parse_list("state:paused").is_none());
+        assert!(TriggerAction::parse_list("unlock:door").is_none());
+        assert!(TriggerAction::parse_list("reward:2@10").is_none());
+        assert!(TriggerAction::parse_list("message:").is_none());
+        assert!(TriggerAction::parse_list("open:door; spawn:enemy").is_none());
+    }
+}
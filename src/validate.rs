@@ -0,0 +1,588 @@
+use std::path::Path;
+
+use bevy::math::Vec2;
+use bevy::utils::HashMap;
+use bevy::utils::HashSet;
+
+use crate::answers::NumericAnswer;
+use crate::campaign::Campaign;
+use crate::checkpoint::STAR_POST_SIZE;
+use crate::interactable::QuizAction;
+use crate::interactable::QuizData;
+use crate::interactable::QuizKind;
+use crate::interactable::MAX_ANSWERS;
+use crate::interactable::MIN_ANSWERS;
+use crate::ldtk;
+use crate::level::LevelData;
+use crate::level::RectData;
+use crate::level::VIEWPORT_SIZE;
+use crate::locks::KEY_SIZE;
+use crate::locks::Unlocks;
+use crate::player::PLAYER_SIZE;
+use crate::questions::QuestionBank;
+use crate::rings::RING_SIZE;
+use crate::tiled;
+use crate::triggers::TriggerAction;
+use crate::triggers::TriggerEvent;
+
+/// Reads rooms the same way the asset loaders do, but straight from disk.
+/// `path` is relative to `asset_root` and may point to a campaign, a room,
+/// a map or an LDtk project (optionally with a `#Level` label).
+pub fn read_levels(asset_root: &Path, path: &str) -> Result<Vec<(String, LevelData)>, String>
+{
+    let mut levels = read_rooms(asset_root, path)?;
+    for (name, level) in levels.iter_mut()
+    {
+        let mut banks = HashMap::new();
+        for bank in level.question_banks()
+        {
+            banks.insert(bank.clone(), read_bank(asset_root, &bank)?);
+        }
+
+        level.fill_questions(&banks).map_err(|error| format!("{name}: {error}"))?;
+    }
+
+    Ok(levels)
+}
+
+/// Reads a `*.questions.ron` file, `path` is relative to `asset_root`.
+pub fn read_bank(asset_root: &Path, path: &str) -> Result<QuestionBank, String>
+{
+    let bytes = std::fs::read(asset_root.join(path)).map_err(|error| format!("{path}: {error}"))?;
+    QuestionBank::parse(&bytes).map_err(|error| format!("{path}: {error}"))
+}
+
+fn read_rooms(asset_root: &Path, path: &str) -> Result<Vec<(String, LevelData)>, String>
+{
+    let (file, label) = match path.split_once('#')
+    {
+        Some((file, label)) => (file, Some(label)),
+        None => (path, None)
+    };
+
+    let bytes = std::fs::read(asset_root.join(file)).map_err(|error| format!("{path}: {error}"))?;
+    let describe = |error: &dyn std::fmt::Display| format!("{path}: {error}");
+
+    if file.ends_with(".campaign.ron")
+    {
+        let campaign = Campaign::parse(&bytes).map_err(|error| describe(&error))?;
+        let mut levels = Vec::new();
+        for level in campaign.level_paths()
+        {
+            levels.extend(read_rooms(asset_root, level)?);
+        }
+
+        Ok(levels)
+    }
+    else if file.ends_with(".level.ron")
+    {
+        Ok(vec![(path.to_string(), LevelData::parse(&bytes).map_err(|error| describe(&error))?)])
+    }
+    else if file.ends_with(".tmx") || file.ends_with(".tmj")
+    {
+        Ok(vec![(path.to_string(), tiled::parse_map(&bytes, Path::new(file)).map_err(|error| describe(&error))?)])
+    }
+    else if file.ends_with(".ldtk")
+    {
+        let levels = ldtk::parse_project(&bytes, Path::new(file)).map_err(|error| describe(&error))?;
+        let levels: Vec<(String, LevelData)> = levels
+            .into_iter()
+            .filter(|(identifier, _)| label.is_none_or(|label| label == identifier))
+            .map(|(identifier, level)| (format!("{file}#{identifier}"), level))
+            .collect();
+
+        if levels.is_empty()
+        {
+            return Err(format!("{path}: no such level in the project"));
+        }
+
+        Ok(levels)
+    }
+    else
+    {
+        Err(format!("{path}: unknown file type"))
+    }
+}
+
+/// Describes how many answers the kind of quiz needs, if `answers` is not that many.
+fn wrong_answer_count(kind: QuizKind, answers: usize) -> Option<String>
+{
+    match kind
+    {
+        QuizKind::TrueFalse if answers != 2 => Some(String::from("2")),
+        QuizKind::FreeText if answers == 0 => Some(String::from("at least 1")),
+        QuizKind::Single | QuizKind::MultiSelect if !(MIN_ANSWERS..=MAX_ANSWERS).contains(&answers) =>
+            Some(format!("{MIN_ANSWERS} to {MAX_ANSWERS}")),
+        _ => None
+    }
+}
+
+/// Describes what keeps a numeric answer from ever being right.
+fn numeric_problems(numeric: Option<&NumericAnswer>) -> Vec<String>
+{
+    let Some(numeric) = numeric else
+    {
+        return vec![String::from("has no number for its answer")];
+    };
+
+    let mut problems = Vec::new();
+    if !numeric.value.is_finite()
+    {
+        problems.push(format!("expects {} which can never be typed", numeric.value));
+    }
+
+    if numeric.absolute < 0. || numeric.relative < 0.
+    {
+        problems.push(String::from("has a negative tolerance"));
+    }
+
+    for (unit, _) in numeric.units.iter().filter(|(_, factor)| !factor.is_finite() || *factor <= 0.)
+    {
+        problems.push(format!("converts `{unit}` with a factor that is not a positive number"));
+    }
+
+    problems
+}
+
+/// Lists questions a quiz could not be built from.
+pub fn validate_bank(bank: &QuestionBank) -> Vec<String>
+{
+    let mut problems = Vec::new();
+    let mut ids = HashSet::new();
+
+    for question in bank.questions.iter()
+    {
+        if !ids.insert(question.id.as_str())
+        {
+            problems.push(format!("question `{}` is listed twice", question.id));
+        }
+
+        if question.prompt.is_empty()
+        {
+            problems.push(format!("question `{}` has no prompt", question.id));
+        }
+
+        // True/false questions may leave their answers out
+        let answers = match question.kind
+        {
+            QuizKind::TrueFalse if question.answers.is_empty() => 2,
+            _ => question.answers.len()
+        };
+
+        if let Some(expected) = wrong_answer_count(question.kind, answers)
+        {
+            problems.push(format!("question `{}` has {answers} answers instead of {expected}", question.id));
+        }
+
+        if question.kind == QuizKind::Numeric
+        {
+            let numeric = question.numeric.clone().or_else(|| question.answers.first().and_then(|answer| NumericAnswer::parse(answer)));
+            for problem in numeric_problems(numeric.as_ref())
+            {
+                problems.push(format!("question `{}` {problem}", question.id));
+            }
+        }
+
+        // Every answer to a free-text question is right, numeric ones are checked by value
+        if question.kind.is_typed()
+        {
+            continue;
+        }
+
+        match question.correct.len()
+        {
+            0 => problems.push(format!("question `{}` has no correct answer", question.id)),
+            1 => {},
+            correct if question.kind != QuizKind::MultiSelect =>
+                problems.push(format!("question `{}` has {correct} correct answers but is not multi-select", question.id)),
+            _ => {}
+        }
+
+        for correct in question.correct.iter().filter(|correct| **correct == 0 || **correct > answers)
+        {
+            problems.push(format!("question `{}` marks answer {correct} as correct but has no such answer", question.id));
+        }
+    }
+
+    problems
+}
+
+/// Lists everything in a room that would make it unplayable.
+pub fn validate_level(level: &LevelData) -> Vec<String>
+{
+    let mut problems = Vec::new();
+
+    for (index, quiz) in level.quizzes.iter().enumerate()
+    {
+        if !quiz.id.is_empty() && level.quizzes[..index].iter().any(|other| other.id == quiz.id)
+        {
+            problems.push(format!("quiz id `{}` is used twice", quiz.id));
+        }
+
+        let correct = quiz.buttons.iter().filter(|button| button.is_correct).count();
+        match quiz.kind
+        {
+            QuizKind::MultiSelect if correct == 0 => problems.push(format!("quiz \"{}\" has no correct answer", quiz.question.text)),
+            QuizKind::MultiSelect | QuizKind::FreeText | QuizKind::Numeric => {},
+            _ if correct != 1 => problems.push(format!("quiz \"{}\" has {correct} correct answers instead of one", quiz.question.text)),
+            _ => {}
+        }
+
+        let answers = if quiz.kind == QuizKind::FreeText { quiz.accepted.len() } else { quiz.buttons.len() };
+        if let Some(expected) = wrong_answer_count(quiz.kind, answers)
+        {
+            problems.push(format!("quiz \"{}\" has {answers} answers instead of {expected}", quiz.question.text));
+        }
+
+        if quiz.accepted.iter().any(|accepted| quiz.normalize.apply(accepted).is_empty())
+        {
+            problems.push(format!("quiz \"{}\" accepts an empty answer", quiz.question.text));
+        }
+
+        if quiz.kind == QuizKind::Numeric
+        {
+            for problem in numeric_problems(quiz.numeric.as_ref())
+            {
+                problems.push(format!("quiz \"{}\" {problem}", quiz.question.text));
+            }
+        }
+
+        let actions = quiz.all_actions();
+        if actions.is_empty()
+        {
+            problems.push(format!("quiz \"{}\" does nothing when answered", quiz.question.text));
+        }
+
+        for gate in actions.iter().filter_map(QuizAction::gate).filter(|gate| !level.gates.iter().any(|candidate| candidate.id == *gate))
+        {
+            problems.push(format!("quiz \"{}\" opens gate `{gate}` which does not exist", quiz.question.text));
+        }
+    }
+
+    for (index, trigger) in level.triggers.iter().enumerate()
+    {
+        if let TriggerEvent::Answered { quiz, .. } = &trigger.when
+        {
+            if quiz.is_empty() || !level.quizzes.iter().any(|candidate| candidate.id == *quiz)
+            {
+                problems.push(format!("trigger {index} waits for quiz `{quiz}` which does not exist"));
+            }
+        }
+
+        if let TriggerEvent::Elapsed(seconds) = trigger.when
+        {
+            if !seconds.is_finite() || seconds < 0.
+            {
+                problems.push(format!("trigger {index} waits {seconds} seconds"));
+            }
+        }
+
+        if trigger.then.is_empty()
+        {
+            problems.push(format!("trigger {index} does nothing when set off"));
+        }
+
+        for action in trigger.then.iter()
+        {
+            match action
+            {
+                TriggerAction::OpenGate(gate) if !level.gates.iter().any(|candidate| candidate.id == *gate) =>
+                    problems.push(format!("trigger {index} opens gate `{gate}` which does not exist")),
+                TriggerAction::Sound(path) if path.is_empty() => problems.push(format!("trigger {index} plays no sound")),
+                _ => {}
+            }
+        }
+    }
+
+    for switch in level.switches.iter().filter(|switch| switch.signal.is_empty())
+    {
+        problems.push(format!("switch at ({}, {}) sends no signal", switch.rect.x, switch.rect.y));
+    }
+
+    // Every key picked up, every quiz answered, every switch on and every trigger set off
+    let actions: Vec<QuizAction> = level.quizzes.iter().flat_map(QuizData::all_actions).collect();
+    let trigger_actions: Vec<&TriggerAction> = level.triggers.iter().flat_map(|trigger| trigger.then.iter()).collect();
+    let sent: HashSet<String> = actions
+        .iter()
+        .filter_map(|action| match action
+        {
+            QuizAction::Signal(id) => Some(id.clone()),
+            _ => None
+        })
+        .chain(trigger_actions.iter().filter_map(|action| match action
+        {
+            TriggerAction::Signal(id) => Some(id.clone()),
+            _ => None
+        }))
+        .collect();
+    let forced: HashSet<&str> = trigger_actions
+        .iter()
+        .filter_map(|action| match action
+        {
+            TriggerAction::OpenGate(id) => Some(id.as_str()),
+            _ => None
+        })
+        .collect();
+
+    let everything = Unlocks
+    {
+        keys: level.keys.iter().map(|key| key.id.clone()).collect(),
+        quizzes: actions.iter().filter_map(QuizAction::gate).map(str::to_string).collect(),
+        signals: level.switches.iter().map(|switch| switch.signal.clone()).chain(sent.iter().cloned()).collect(),
+        sent,
+        levers: Default::default()
+    };
+
+    let listened = level.gates.iter().flat_map(|gate| gate.lock.signals())
+        .chain(level.switched_hazards.iter().map(|hazard| hazard.signal.as_str()))
+        .chain(level.platforms.iter().map(|platform| platform.signal.as_str()).filter(|signal| !signal.is_empty()))
+        .chain(level.triggers.iter().filter_map(|trigger| match &trigger.when
+        {
+            TriggerEvent::Signal { signal, .. } => Some(signal.as_str()),
+            _ => None
+        }));
+
+    let mut missing: Vec<&str> = listened.filter(|signal| !everything.signals.contains(*signal)).collect();
+    missing.sort();
+    missing.dedup();
+    for signal in missing
+    {
+        problems.push(format!("signal `{signal}` is not sent by any switch, quiz or trigger"));
+    }
+
+    for gate in level.gates.iter()
+    {
+        for key in gate.lock.keys().into_iter().filter(|key| !everything.keys.contains(*key))
+        {
+            problems.push(format!("gate `{}` needs key `{key}` which does not exist", gate.id));
+        }
+
+        if !everything.is_open(&gate.id, &gate.lock) && !forced.contains(gate.id.as_str())
+        {
+            problems.push(format!("gate `{}` is never opened", gate.id));
+        }
+    }
+
+    let spawn = RectData::centered(Vec2::new(level.player_start.x, level.player_start.y), PLAYER_SIZE);
+    for hazard in level.hazards.iter().filter(|hazard| hazard.overlaps(&spawn))
+    {
+        problems.push(format!("hazard at ({}, {}) overlaps the player start", hazard.x, hazard.y));
+    }
+
+    for post in level.checkpoints.iter()
+    {
+        let respawn = RectData::centered(Vec2::new(post.x, post.y - (STAR_POST_SIZE.y - PLAYER_SIZE.y) / 2.), PLAYER_SIZE);
+        if level.hazards.iter().any(|hazard| hazard.overlaps(&respawn))
+        {
+            problems.push(format!("checkpoint at ({}, {}) respawns the player inside a hazard", post.x, post.y));
+        }
+    }
+
+    for platform in level.platforms.iter().filter(|platform| !platform.path.is_empty() && platform.speed <= 0.)
+    {
+        problems.push(format!("platform at ({}, {}) has a path but no speed", platform.rect.x, platform.rect.y));
+    }
+
+    // Enemies do not fall, they need something to walk on from the start
+    for enemy in level.enemies.iter()
+    {
+        let below = RectData::centered(Vec2::new(enemy.rect.x, enemy.rect.min().y - 1.), Vec2::new(enemy.rect.width, 2.));
+        let supported = level.ground.iter()
+            .chain(level.walls.iter())
+            .chain(level.gates.iter().map(|gate| &gate.rect))
+            .any(|ground| ground.overlaps(&below));
+
+        if !supported
+        {
+            problems.push(format!("enemy at ({}, {}) is not standing on ground", enemy.rect.x, enemy.rect.y));
+        }
+    }
+
+    for spring in level.springs.iter().filter(|spring| spring.impulse.x == 0. && spring.impulse.y == 0.)
+    {
+        problems.push(format!("spring at ({}, {}) has no impulse", spring.rect.x, spring.rect.y));
+    }
+
+    let bounds = level.camera_bounds();
+    let platform_stops: Vec<RectData> = level.platforms.iter().flat_map(|platform| platform.stops()).collect();
+    let rewards = actions.iter().filter_map(|action| match action
+    {
+        QuizAction::Reward { x, y, .. } => Some(Vec2::new(*x, *y)),
+        _ => None
+    })
+    .chain(trigger_actions.iter().filter_map(|action| match action
+    {
+        TriggerAction::Reward { x, y, .. } => Some(Vec2::new(*x, *y)),
+        _ => None
+    }));
+
+    let rings: Vec<RectData> = level.rings
+        .iter()
+        .map(|ring| Vec2::new(ring.x, ring.y))
+        .chain(rewards)
+        .map(|ring| RectData::centered(ring, RING_SIZE))
+        .collect();
+    let keys: Vec<RectData> = level.keys.iter().map(|key| RectData::centered(Vec2::new(key.x, key.y), KEY_SIZE)).collect();
+    let trigger_areas = level.triggers
+        .iter()
+        .filter_map(|trigger| match &trigger.when
+        {
+            TriggerEvent::Enter(area) => Some(area),
+            _ => None
+        })
+        .chain(trigger_actions.iter().filter_map(|action| match action
+        {
+            TriggerAction::SpawnEnemy(enemy) => Some(&enemy.rect),
+            _ => None
+        }));
+    let colliders = level.ground.iter()
+        .chain(level.walls.iter())
+        .chain(level.hazards.iter())
+        .chain(level.gates.iter().map(|gate| &gate.rect))
+        .chain(level.quizzes.iter().map(|quiz| &quiz.rect))
+        .chain(platform_stops.iter())
+        .chain(level.enemies.iter().map(|enemy| &enemy.rect))
+        .chain(level.springs.iter().map(|spring| &spring.rect))
+        .chain(level.switches.iter().map(|switch| &switch.rect))
+        .chain(level.switched_hazards.iter().map(|hazard| &hazard.rect))
+        .chain(level.boxes.iter())
+        .chain(rings.iter())
+        .chain(keys.iter())
+        .chain(trigger_areas)
+        .chain(std::iter::once(&level.goal));
+
+    for collider in colliders.filter(|collider| !collider.overlaps(&bounds))
+    {
+        problems.push(format!("collider at ({}, {}) is outside the room bounds", collider.x, collider.y));
+    }
+
+    if bounds.width < VIEWPORT_SIZE.x || bounds.height < VIEWPORT_SIZE.y
+    {
+        problems.push(format!("bounds {}x{} are smaller than the screen", bounds.width, bounds.height));
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::enemy::EnemyData;
+    use crate::level::PointData;
+    use crate::locks::GateData;
+    use crate::locks::LockData;
+    use crate::triggers::TriggerData;
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> RectData
+    {
+        RectData { x, y, width, height }
+    }
+
+    /// A floor to walk along from the start to the goal, behind a gate a quiz opens.
+    fn room() -> LevelData
+    {
+        LevelData
+        {
+            player_start: PointData { x: -100., y: 0. },
+            ground: vec![rect(0., -40., 300., 10.)],
+            gates: vec![GateData { id: String::from("door"), rect: rect(0., 40., 10., 150.), ..Default::default() }],
+            quizzes: vec![QuizData::from_answers(
+                rect(-50., -25., 10., 10.),
+                Some(String::from("door")),
+                QuizKind::Single,
+                String::from("2 + 2"),
+                &[String::from("4"), String::from("5")],
+                &[1]
+            )],
+            goal: rect(100., -30., 10., 10.),
+            ..Default::default()
+        }
+    }
+
+    fn assert_reports(problems: &[String], expected: &str)
+    {
+        assert!(problems.iter().any(|problem| problem == expected), "expected `{expected}` in {problems:?}");
+    }
+
+    #[test]
+    fn playable_room_has_no_problems()
+    {
+        assert_eq!(validate_level(&room()), Vec::<String>::new());
+    }
+
+    #[test]
+    fn reports_missing_gate()
+    {
+        let mut level = room();
+        level.quizzes[0].gate = Some(String::from("nowhere"));
+        let problems = validate_level(&level);
+        assert_reports(&problems, "quiz \"2 + 2\" opens gate `nowhere` which does not exist");
+        assert_reports(&problems, "gate `door` is never opened");
+    }
+
+    #[test]
+    fn reports_unknown_and_repeated_quiz_ids()
+    {
+        let mut level = room();
+        level.quizzes[0].id = String::from("sum");
+        level.quizzes.push(level.quizzes[0].clone());
+        level.triggers.push(TriggerData
+        {
+            when: TriggerEvent::Answered { quiz: String::from("product"), correct: true },
+            then: vec![TriggerAction::Signal(String::from("lift"))],
+            repeat: false
+        });
+        let problems = validate_level(&level);
+        assert_reports(&problems, "quiz id `sum` is used twice");
+        assert_reports(&problems, "trigger 0 waits for quiz `product` which does not exist");
+    }
+
+    #[test]
+    fn reports_negative_delay()
+    {
+        let mut level = room();
+        level.triggers.push(TriggerData
+        {
+            when: TriggerEvent::Elapsed(-2.),
+            then: vec![TriggerAction::Signal(String::from("lift"))],
+            repeat: false
+        });
+        assert_reports(&validate_level(&level), "trigger 0 waits -2 seconds");
+    }
+
+    #[test]
+    fn reports_unsent_signal()
+    {
+        let mut level = room();
+        level.gates[0].lock = LockData::Signal(String::from("lift"));
+        assert_reports(&validate_level(&level), "signal `lift` is not sent by any switch, quiz or trigger");
+    }
+
+    #[test]
+    fn reports_hazard_on_spawn()
+    {
+        let mut level = room();
+        level.hazards.push(rect(-100., -5., 20., 10.));
+        assert_reports(&validate_level(&level), "hazard at (-100, -5) overlaps the player start");
+    }
+
+    #[test]
+    fn reports_unsupported_enemy()
+    {
+        let mut level = room();
+        level.enemies.push(EnemyData { rect: rect(50., 40., 10., 10.), speed: 20., ..Default::default() });
+        assert_reports(&validate_level(&level), "enemy at (50, 40) is not standing on ground");
+
+        level.enemies[0].rect.y = -30.;
+        assert_eq!(validate_level(&level), Vec::<String>::new());
+    }
+
+    #[test]
+    fn reports_collider_out_of_bounds()
+    {
+        let mut level = room();
+        level.walls.push(rect(500., 0., 10., 100.));
+        assert_reports(&validate_level(&level), "collider at (500, 0) is outside the room bounds");
+    }
+}